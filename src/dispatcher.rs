@@ -0,0 +1,92 @@
+use {Evented, Poll, Token};
+use event::{Event, EventSet, PollOpt};
+use std::{fmt, io};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Dispatches events from a `Poll` to callbacks registered per `Token`.
+///
+/// `Dispatcher` is a thin convenience layer for programs that would rather
+/// associate a closure with each IO handle than match on tokens by hand. It
+/// owns its `Poll` instance, and therefore its events buffer.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use mio::*;
+/// use mio::udp::*;
+///
+/// let mut dispatcher = Dispatcher::new().unwrap();
+/// let sock = UdpSocket::bound(&"127.0.0.1:0".parse().unwrap()).unwrap();
+///
+/// dispatcher.register(&sock, Token(0), EventSet::readable(), PollOpt::edge(), |event| {
+///     println!("ready: {:?}", event.kind());
+/// }).unwrap();
+///
+/// dispatcher.run_once(None).unwrap();
+/// ```
+pub struct Dispatcher {
+    poll: Poll,
+    handlers: HashMap<Token, Box<FnMut(&Event)>>,
+}
+
+impl Dispatcher {
+    pub fn new() -> io::Result<Dispatcher> {
+        Ok(Dispatcher {
+            poll: try!(Poll::new()),
+            handlers: HashMap::new(),
+        })
+    }
+
+    /// Registers an IO handle and the callback to invoke whenever an event is
+    /// received for `token`.
+    ///
+    /// Registering a new handle with a token that is already in use replaces
+    /// the previous callback.
+    pub fn register<E: ?Sized, F>(&mut self, io: &E, token: Token, interest: EventSet, opts: PollOpt, handler: F) -> io::Result<()>
+        where E: Evented, F: FnMut(&Event) + 'static
+    {
+        try!(self.poll.register(io, token, interest, opts));
+        self.handlers.insert(token, Box::new(handler));
+        Ok(())
+    }
+
+    pub fn reregister<E: ?Sized>(&mut self, io: &E, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()>
+        where E: Evented
+    {
+        self.poll.reregister(io, token, interest, opts)
+    }
+
+    /// Deregisters an IO handle and drops the callback associated with
+    /// `token`.
+    pub fn deregister<E: ?Sized>(&mut self, io: &E, token: Token) -> io::Result<()>
+        where E: Evented
+    {
+        try!(self.poll.deregister(io));
+        self.handlers.remove(&token);
+        self.poll.remove_token(token);
+        Ok(())
+    }
+
+    /// Polls once and invokes the callback of every token that received an
+    /// event. Returns the number of events received.
+    ///
+    /// Events for tokens without a callback are ignored.
+    pub fn run_once(&mut self, timeout: Option<Duration>) -> io::Result<usize> {
+        let cnt = try!(self.poll.poll(timeout));
+
+        for event in self.poll.events() {
+            if let Some(handler) = self.handlers.get_mut(&event.token()) {
+                (&mut **handler)(&event);
+            }
+        }
+
+        Ok(cnt)
+    }
+}
+
+impl fmt::Debug for Dispatcher {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Dispatcher {{ handlers: {} }}", self.handlers.len())
+    }
+}
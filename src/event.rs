@@ -25,9 +25,34 @@ impl PollOpt {
         PollOpt(0x080)
     }
 
+    /// Requests exclusive wakeups for a handle registered with several `Poll`
+    /// instances, so that only one of them is woken on readiness.
+    ///
+    /// Only supported on Linux 4.5 and newer (`EPOLLEXCLUSIVE`), where it
+    /// can only be passed to `register` and can't be combined with
+    /// `oneshot`; reregistering an exclusive handle fails. The kernel also
+    /// refuses `EPOLLRDHUP` for such handles, so `hup` is only reported once
+    /// both directions are closed. Other platforms ignore it.
+    #[inline]
+    pub fn exclusive() -> PollOpt {
+        PollOpt(0x100)
+    }
+
+    /// Removes the registration once its first event has been returned by
+    /// `poll`, so that the handle does not need to be deregistered.
+    ///
+    /// Unlike `oneshot`, which only disables the registration until it is
+    /// rearmed with `reregister`, the handle has to be registered again to
+    /// receive further events. On Windows this behaves like `oneshot`.
+    #[inline]
+    pub fn auto_deregister() -> PollOpt {
+        PollOpt(0x200)
+    }
+
     #[inline]
     pub fn all() -> PollOpt {
-        PollOpt::edge() | PollOpt::level() | PollOpt::oneshot()
+        PollOpt::edge() | PollOpt::level() | PollOpt::oneshot() | PollOpt::exclusive() |
+            PollOpt::auto_deregister()
     }
 
     #[inline]
@@ -45,6 +70,16 @@ impl PollOpt {
         self.contains(PollOpt::oneshot())
     }
 
+    #[inline]
+    pub fn is_exclusive(&self) -> bool {
+        self.contains(PollOpt::exclusive())
+    }
+
+    #[inline]
+    pub fn is_auto_deregister(&self) -> bool {
+        self.contains(PollOpt::auto_deregister())
+    }
+
     #[inline]
     pub fn bits(&self) -> usize {
         self.0
@@ -117,7 +152,9 @@ impl fmt::Debug for PollOpt {
         let flags = [
             (PollOpt::edge(), "Edge-Triggered"),
             (PollOpt::level(), "Level-Triggered"),
-            (PollOpt::oneshot(), "OneShot")];
+            (PollOpt::oneshot(), "OneShot"),
+            (PollOpt::exclusive(), "Exclusive"),
+            (PollOpt::auto_deregister(), "AutoDeregister")];
 
         for &(flag, msg) in &flags {
             if self.contains(flag) {
@@ -155,6 +192,10 @@ impl EventSet {
         EventSet(0x004)
     }
 
+    /// The peer has closed its end of the connection.
+    ///
+    /// On Unix platforms this is reported for handles registered with
+    /// either `readable` or `hup` interest.
     #[inline]
     pub fn hup() -> EventSet {
         EventSet(0x008)
@@ -212,6 +253,135 @@ impl EventSet {
     pub fn contains(&self, other: EventSet) -> bool {
         (*self & other) == other
     }
+
+    /// Returns the flags set in `self` that are not set in `old`.
+    ///
+    /// Useful when reregistering, to find out what interest was gained.
+    #[inline]
+    pub fn added_since(self, old: EventSet) -> EventSet {
+        self - old
+    }
+
+    /// Returns the flags set in `old` that are no longer set in `self`.
+    #[inline]
+    pub fn removed_since(self, old: EventSet) -> EventSet {
+        old - self
+    }
+
+    /// Returns the canonical form of `self`, with any bits that do not
+    /// correspond to a known flag cleared.
+    ///
+    /// `none()` is the empty set, so OR-ing it into another set is a no-op,
+    /// and normalized sets can be safely compared with `==`.
+    #[inline]
+    pub fn normalize(self) -> EventSet {
+        self & EventSet::all()
+    }
+
+    /// Returns an iterator yielding each flag set in `self` as a singleton
+    /// `EventSet`.
+    #[inline]
+    pub fn iter(&self) -> EventSetIter {
+        EventSetIter { rem: self.0 }
+    }
+
+    /// Returns an iterator yielding each flag set in `self` in the order in
+    /// which handlers usually want to process them: error, readable,
+    /// writable, then hup.
+    ///
+    /// Reading before handling hup makes sure data sent by the peer before
+    /// it closed the connection is not lost.
+    #[inline]
+    pub fn priority_order(&self) -> PriorityOrder {
+        PriorityOrder { events: *self, pos: 0 }
+    }
+}
+
+/// The readable / writable subset of an `EventSet`.
+///
+/// This is a smaller vocabulary than `EventSet`, for libraries that only
+/// need to express read and write interest.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Interest {
+    Readable,
+    Writable,
+    Both,
+}
+
+impl From<Interest> for EventSet {
+    fn from(interest: Interest) -> EventSet {
+        match interest {
+            Interest::Readable => EventSet::readable(),
+            Interest::Writable => EventSet::writable(),
+            Interest::Both => EventSet::readable() | EventSet::writable(),
+        }
+    }
+}
+
+/// The `hup` and `error` flags are ignored, so a set with neither
+/// `readable` nor `writable` converts to `None`.
+impl From<EventSet> for Option<Interest> {
+    fn from(events: EventSet) -> Option<Interest> {
+        match (events.is_readable(), events.is_writable()) {
+            (true, true) => Some(Interest::Both),
+            (true, false) => Some(Interest::Readable),
+            (false, true) => Some(Interest::Writable),
+            (false, false) => None,
+        }
+    }
+}
+
+/// Iterator over the individual flags of an `EventSet`, see `EventSet::iter`.
+#[derive(Debug, Clone)]
+pub struct EventSetIter {
+    rem: usize,
+}
+
+impl Iterator for EventSetIter {
+    type Item = EventSet;
+
+    fn next(&mut self) -> Option<EventSet> {
+        if self.rem == 0 {
+            return None;
+        }
+
+        // Isolate the lowest set bit
+        let bit = self.rem & (!self.rem + 1);
+        self.rem &= !bit;
+
+        Some(EventSet(bit))
+    }
+}
+
+/// Iterator over the flags of an `EventSet` by priority, see
+/// `EventSet::priority_order`.
+#[derive(Debug, Clone)]
+pub struct PriorityOrder {
+    events: EventSet,
+    pos: usize,
+}
+
+impl Iterator for PriorityOrder {
+    type Item = EventSet;
+
+    fn next(&mut self) -> Option<EventSet> {
+        let order = [
+            EventSet::error(),
+            EventSet::readable(),
+            EventSet::writable(),
+            EventSet::hup()];
+
+        while self.pos < order.len() {
+            let flag = order[self.pos];
+            self.pos += 1;
+
+            if self.events.contains(flag) {
+                return Some(flag);
+            }
+        }
+
+        None
+    }
 }
 
 impl ops::BitOr for EventSet {
@@ -285,7 +455,9 @@ impl fmt::Debug for EventSet {
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Event {
     kind: EventSet,
-    token: Token
+    token: Token,
+    #[cfg(feature = "timestamp")]
+    timestamp: u64,
 }
 
 /// Event represents the raw event that the OS-specific selector
@@ -296,10 +468,21 @@ pub struct Event {
 /// Selector when they have events to report.
 impl Event {
     /// Create a new Event.
+    #[cfg(not(feature = "timestamp"))]
+    pub fn new(kind: EventSet, token: Token) -> Event {
+        Event {
+            kind: kind,
+            token: token,
+        }
+    }
+
+    /// Create a new Event.
+    #[cfg(feature = "timestamp")]
     pub fn new(kind: EventSet, token: Token) -> Event {
         Event {
             kind: kind,
             token: token,
+            timestamp: 0,
         }
     }
 
@@ -310,6 +493,39 @@ impl Event {
     pub fn token(&self) -> Token {
         self.token
     }
+
+    /// Returns the time, in nanoseconds on the clock used by
+    /// `time::precise_time_ns`, at which the event was received from the OS.
+    ///
+    /// Only available with the `timestamp` feature.
+    #[cfg(feature = "timestamp")]
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Shorthand for `self.kind().is_readable()`.
+    #[inline]
+    pub fn is_readable(&self) -> bool {
+        self.kind.is_readable()
+    }
+
+    /// Shorthand for `self.kind().is_writable()`.
+    #[inline]
+    pub fn is_writable(&self) -> bool {
+        self.kind.is_writable()
+    }
+
+    /// Shorthand for `self.kind().is_hup()`.
+    #[inline]
+    pub fn is_hup(&self) -> bool {
+        self.kind.is_hup()
+    }
+
+    /// Shorthand for `self.kind().is_error()`.
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.kind.is_error()
+    }
 }
 
 // Used internally to mutate an `Event` in place
@@ -318,3 +534,91 @@ impl Event {
 pub fn kind_mut(event: &mut Event) -> &mut EventSet {
     &mut event.kind
 }
+
+#[cfg(feature = "timestamp")]
+pub fn set_timestamp(event: &mut Event, timestamp: u64) {
+    event.timestamp = timestamp;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Event, EventSet, Interest};
+    use Token;
+
+    #[test]
+    pub fn test_event_set_iter() {
+        let flags: Vec<EventSet> = (EventSet::readable() | EventSet::hup()).iter().collect();
+        assert_eq!(vec![EventSet::readable(), EventSet::hup()], flags);
+
+        assert_eq!(0, EventSet::none().iter().count());
+        assert_eq!(4, EventSet::all().iter().count());
+        assert_eq!(EventSet::all(), EventSet::all().iter().fold(EventSet::none(), |a, b| a | b));
+    }
+
+    #[test]
+    pub fn test_event_set_normalize() {
+        let events = EventSet::none() | EventSet::readable();
+
+        assert!(events.is_readable());
+        assert_eq!(EventSet::readable(), events);
+        assert_eq!(EventSet::readable(), events.normalize());
+        assert_eq!(EventSet::none(), EventSet::none().normalize());
+        assert_eq!(EventSet::all(), EventSet(!0).normalize());
+    }
+
+    #[test]
+    pub fn test_event_set_diff() {
+        let old = EventSet::readable() | EventSet::hup();
+        let new = EventSet::readable() | EventSet::writable();
+
+        assert_eq!(EventSet::writable(), new.added_since(old));
+        assert_eq!(EventSet::hup(), new.removed_since(old));
+
+        assert_eq!(EventSet::none(), new.added_since(new));
+        assert_eq!(EventSet::none(), new.removed_since(new));
+
+        let mut events = new;
+        events.remove(EventSet::readable());
+        assert_eq!(EventSet::writable(), events);
+    }
+
+    #[test]
+    pub fn test_event_set_priority_order() {
+        let flags: Vec<EventSet> = EventSet::all().priority_order().collect();
+        assert_eq!(vec![EventSet::error(), EventSet::readable(), EventSet::writable(), EventSet::hup()], flags);
+
+        let flags: Vec<EventSet> = (EventSet::hup() | EventSet::readable()).priority_order().collect();
+        assert_eq!(vec![EventSet::readable(), EventSet::hup()], flags);
+
+        assert_eq!(0, EventSet::none().priority_order().count());
+    }
+
+    #[test]
+    pub fn test_event_accessors() {
+        let event = Event::new(EventSet::readable() | EventSet::hup(), Token(7));
+
+        assert_eq!(Token(7), event.token());
+        assert_eq!(EventSet::readable() | EventSet::hup(), event.kind());
+        assert!(event.is_readable());
+        assert!(!event.is_writable());
+        assert!(event.is_hup());
+        assert!(!event.is_error());
+
+        let copy = event;
+        assert_eq!(event, copy);
+    }
+
+    #[test]
+    pub fn test_interest_round_trip() {
+        for &interest in &[Interest::Readable, Interest::Writable, Interest::Both] {
+            let back: Option<Interest> = EventSet::from(interest).into();
+            assert_eq!(Some(interest), back);
+        }
+
+        let none: Option<Interest> = (EventSet::hup() | EventSet::error()).into();
+        assert_eq!(None, none);
+
+        let readable: Option<Interest> = (EventSet::readable() | EventSet::hup()).into();
+        assert_eq!(Some(Interest::Readable), readable);
+    }
+}
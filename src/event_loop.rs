@@ -193,6 +193,9 @@ impl<H: Handler> EventLoop<H> {
     /// Returns a handle to the timeout that can be used to cancel the timeout
     /// using [#clear_timeout](#method.clear_timeout).
     ///
+    /// The delay is measured on a monotonic clock, so it is not affected by
+    /// changes to the system time.
+    ///
     /// # Example
     /// ```
     /// use mio::{EventLoop, Handler};
@@ -219,6 +222,19 @@ impl<H: Handler> EventLoop<H> {
         self.timer.timeout_ms(token, convert::millis(delay))
     }
 
+    /// Schedules `token` to be passed to `Handler::timeout` every `period`,
+    /// starting `period` from now, until the returned `Timeout` is cleared
+    /// with `clear_timeout`.
+    ///
+    /// Like one-shot timeouts, the interval has the granularity of the timer
+    /// tick. If the event loop falls behind, missed expirations are skipped
+    /// rather than delivered in a burst.
+    pub fn interval(&mut self, token: H::Timeout, period: Duration) -> TimerResult<Timeout>
+        where H::Timeout: Clone
+    {
+        self.timer.interval_ms(token, convert::millis(period))
+    }
+
     /// If the supplied timeout has not been triggered, cancel it such that it
     /// will not be triggered in the future.
     pub fn clear_timeout(&mut self, timeout: &Timeout) -> bool {
@@ -397,6 +413,21 @@ impl <H: Handler> Drop for EventLoop<H> {
 }
 
 /// Sends messages to the EventLoop from other threads.
+///
+/// `Sender<M>` is `Send` and `Sync` if and only if `M` is `Send`. Messages are
+/// moved into the queue and out of it on the event loop thread, they are
+/// never shared, so `M` does not need to be `Sync`.
+///
+/// A sender of messages that cannot cross threads is not `Send` itself:
+///
+/// ```compile_fail
+/// use std::rc::Rc;
+/// use mio::Sender;
+///
+/// fn assert_send<T: Send>() {}
+///
+/// assert_send::<Sender<Rc<u8>>>();
+/// ```
 pub struct Sender<M> {
     notify: Notify<M>
 }
@@ -421,6 +452,54 @@ impl<M> Sender<M> {
     pub fn send(&self, msg: M) -> Result<(), NotifyError<M>> {
         self.notify.notify(msg)
     }
+
+    /// Sends a message like `send`, returning the number of messages
+    /// waiting to be processed by the event loop once it was queued.
+    ///
+    /// Producers can use the count to slow down before the queue fills up.
+    /// Other senders may be racing for the same queue, so it is only a
+    /// snapshot.
+    pub fn send_counted(&self, msg: M) -> Result<usize, NotifyError<M>> {
+        self.notify.notify_counted(msg)
+    }
+
+    /// Sends every message yielded by `msgs`, waking up the event loop at
+    /// most once for the whole batch.
+    ///
+    /// Returns the number of messages sent. If the queue fills up the
+    /// remaining messages are not consumed from the iterator, and the error
+    /// carries the number of messages sent along with the first message that
+    /// could not be.
+    pub fn send_all<I>(&self, msgs: I) -> Result<usize, (usize, NotifyError<M>)>
+        where I: IntoIterator<Item=M>
+    {
+        self.notify.notify_all(msgs.into_iter())
+    }
+
+    /// Returns `true` once the receiving `EventLoop` has been dropped.
+    ///
+    /// After this returns `true`, every call to `send` fails with
+    /// `NotifyError::Closed`.
+    pub fn is_closed(&self) -> bool {
+        self.notify.is_closed()
+    }
+
+    /// Returns the maximum number of messages that can be queued.
+    ///
+    /// The notify queue is always bounded, so this is never `None`. The
+    /// capacity configured with `EventLoopBuilder::notify_capacity` is
+    /// rounded up to a power of two.
+    pub fn capacity(&self) -> Option<usize> {
+        Some(self.notify.capacity())
+    }
+
+    /// Returns the number of messages that were refused with
+    /// `NotifyError::Full` because the notify queue was at capacity.
+    ///
+    /// The count is shared by all clones of this `Sender`.
+    pub fn dropped_count(&self) -> usize {
+        self.notify.dropped_count()
+    }
 }
 
 #[cfg(test)]
@@ -32,6 +32,29 @@ pub trait TryRead {
     }
 
     fn try_read(&mut self, buf: &mut [u8]) -> Result<Option<usize>>;
+
+    /// Reads into `buf[*pos..]` until it is full or the read would block,
+    /// advancing `pos` past the bytes read.
+    ///
+    /// Returns `Ok(true)` once `buf` has been filled and `Ok(false)` if the
+    /// read would block, in which case the call should be repeated with the
+    /// same `pos` on the next readable event. Reaching EOF before `buf` is
+    /// full is an `UnexpectedEof` error.
+    fn try_read_exact(&mut self, buf: &mut [u8], pos: &mut usize) -> Result<bool> {
+        while *pos < buf.len() {
+            match self.try_read(&mut buf[*pos..]) {
+                Ok(Some(0)) => {
+                    return Err(Error::new(ErrorKind::UnexpectedEof, "failed to fill whole buffer"));
+                }
+                Ok(Some(n)) => *pos += n,
+                Ok(None) => return Ok(false),
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(true)
+    }
 }
 
 pub trait TryWrite {
@@ -48,6 +71,28 @@ pub trait TryWrite {
     }
 
     fn try_write(&mut self, buf: &[u8]) -> Result<Option<usize>>;
+
+    /// Writes `buf[*pos..]` until it has all been written or the write would
+    /// block, advancing `pos` past the bytes written.
+    ///
+    /// Returns `Ok(true)` once all of `buf` has been written and `Ok(false)`
+    /// if the write would block, in which case the call should be repeated
+    /// with the same `pos` on the next writable event.
+    fn try_write_all(&mut self, buf: &[u8], pos: &mut usize) -> Result<bool> {
+        while *pos < buf.len() {
+            match self.try_write(&buf[*pos..]) {
+                Ok(Some(0)) => {
+                    return Err(Error::new(ErrorKind::WriteZero, "failed to write whole buffer"));
+                }
+                Ok(Some(n)) => *pos += n,
+                Ok(None) => return Ok(false),
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(true)
+    }
 }
 
 impl<T: Read> TryRead for T {
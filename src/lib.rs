@@ -100,6 +100,7 @@ extern crate log;
 #[cfg(test)]
 extern crate env_logger;
 
+mod dispatcher;
 mod event;
 mod event_loop;
 mod handler;
@@ -108,14 +109,22 @@ mod net;
 mod notify;
 mod poll;
 mod sys;
+mod throttle;
 mod timer;
 mod token;
+mod tracing;
 mod util;
 
+pub use dispatcher::{
+    Dispatcher,
+};
 pub use event::{
     PollOpt,
     EventSet,
+    EventSetIter,
     Event,
+    Interest,
+    PriorityOrder,
 };
 pub use event_loop::{
     EventLoop,
@@ -160,6 +169,13 @@ pub use notify::{
 pub use poll::{
     Poll,
     Events,
+    Label,
+};
+#[cfg(unix)]
+pub use poll::Registered;
+pub use throttle::{
+    ThrottledSender,
+    ThrottleError,
 };
 pub use timer::{
     Timeout,
@@ -169,6 +185,9 @@ pub use timer::{
 pub use token::{
     Token,
 };
+pub use tracing::{
+    TracingSender,
+};
 #[cfg(unix)]
 pub use sys::Io;
 
@@ -179,7 +198,21 @@ mod convert {
     const NANOS_PER_MILLI: u32 = 1_000_000;
     const MILLIS_PER_SEC: u64 = 1_000;
 
+    /// Convert a `Duration` to milliseconds, saturating at `u64::MAX`.
     pub fn millis(duration: Duration) -> u64 {
-        duration.as_secs() * MILLIS_PER_SEC + ((duration.subsec_nanos() / NANOS_PER_MILLI) as u64)
+        let millis = (duration.subsec_nanos() / NANOS_PER_MILLI) as u64;
+        duration.as_secs().saturating_mul(MILLIS_PER_SEC).saturating_add(millis)
+    }
+
+    /// Like `millis`, but rounds any fractional millisecond up so that a
+    /// non-zero duration never becomes zero.
+    pub fn millis_ceil(duration: Duration) -> u64 {
+        let millis = millis(duration);
+
+        if duration.subsec_nanos() % NANOS_PER_MILLI == 0 {
+            millis
+        } else {
+            millis.saturating_add(1)
+        }
     }
 }
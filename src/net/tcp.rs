@@ -1,5 +1,6 @@
 use std::io::{Read, Write};
 use std::net::{self, SocketAddr, SocketAddrV4, SocketAddrV6, Ipv4Addr, Ipv6Addr};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use net2::TcpBuilder;
 
@@ -18,6 +19,12 @@ pub struct TcpStream {
 
 pub use std::net::Shutdown;
 
+/// Returns `true` if `err` reports that the process or the system ran out of
+/// file descriptors, as `accept` does with `EMFILE` or `ENFILE`.
+pub fn is_fd_exhaustion(err: &io::Error) -> bool {
+    sys::is_fd_exhaustion(err)
+}
+
 impl TcpStream {
     /// Create a new TCP stream an issue a non-blocking connect to the specified
     /// address.
@@ -81,10 +88,33 @@ impl TcpStream {
         self.sys.shutdown(how)
     }
 
+    /// Reads data from the socket without removing it from the receive
+    /// queue, so that a subsequent read returns the same bytes.
+    ///
+    /// Returns a `WouldBlock` error if no data is available.
+    pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.sys.peek(buf)
+    }
+
+    /// Returns `true` if a write can currently make progress without
+    /// blocking.
+    ///
+    /// This does not write anything to the socket. It lets a writer that
+    /// still has data queued after a writable event skip a `write` that
+    /// would only fail with `WouldBlock`. The answer is a snapshot and may be
+    /// stale by the time it is acted on.
+    pub fn poll_write_ready(&self) -> io::Result<bool> {
+        self.sys.poll_write_ready()
+    }
+
     pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
         self.sys.set_nodelay(nodelay)
     }
 
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.sys.nodelay()
+    }
+
     pub fn set_keepalive(&self, seconds: Option<u32>) -> io::Result<()> {
         self.sys.set_keepalive(seconds)
     }
@@ -141,6 +171,105 @@ impl Evented for TcpStream {
     }
 }
 
+/*
+ *
+ * ===== Connecting =====
+ *
+ */
+
+/// A `TcpStream` whose non-blocking connect has not yet completed.
+///
+/// Register the `Connecting` for writable interest and call `poll_connect`
+/// whenever it is reported ready. `poll_connect` returns `Ok(None)` while the
+/// connect is still in progress, the connected `TcpStream` once it has been
+/// established, and the socket error if it failed. Once the connect has
+/// failed, every later call returns the same error.
+///
+/// The returned stream keeps the registration made through the `Connecting`.
+#[derive(Debug)]
+pub struct Connecting {
+    stream: Option<TcpStream>,
+    // `SO_ERROR` is cleared once read, so a failure is remembered here
+    error: Option<io::Error>,
+}
+
+impl Connecting {
+    /// Issue a non-blocking connect to the specified address.
+    pub fn connect(addr: &SocketAddr) -> io::Result<Connecting> {
+        TcpStream::connect(addr).map(Connecting::from_stream)
+    }
+
+    /// Wrap a `TcpStream` that has a connect in progress.
+    pub fn from_stream(stream: TcpStream) -> Connecting {
+        Connecting {
+            stream: Some(stream),
+            error: None,
+        }
+    }
+
+    /// Checks whether the connect has completed.
+    pub fn poll_connect(&mut self) -> io::Result<Option<TcpStream>> {
+        if let Some(ref err) = self.error {
+            return Err(copy_error(err));
+        }
+
+        let res = {
+            let stream = try!(self.stream());
+            is_connected(stream)
+        };
+
+        match res {
+            Ok(true) => Ok(self.stream.take()),
+            Ok(false) => Ok(None),
+            Err(e) => {
+                self.error = Some(copy_error(&e));
+                Err(e)
+            }
+        }
+    }
+
+    fn stream(&self) -> io::Result<&TcpStream> {
+        self.stream.as_ref().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "connection already established")
+        })
+    }
+}
+
+fn is_connected(stream: &TcpStream) -> io::Result<bool> {
+    // A failed connect is reported through `SO_ERROR`
+    try!(stream.take_socket_error());
+
+    match stream.peer_addr() {
+        Ok(..) => Ok(true),
+        Err(ref e) if e.kind() == io::ErrorKind::NotConnected => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// `io::Error` is not `Clone`
+fn copy_error(err: &io::Error) -> io::Error {
+    match err.raw_os_error() {
+        Some(code) => io::Error::from_raw_os_error(code),
+        None => io::Error::new(err.kind(), err.to_string()),
+    }
+}
+
+impl Evented for Connecting {
+    fn register(&self, poll: &mut Poll, token: Token,
+                interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        try!(self.stream()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(&self, poll: &mut Poll, token: Token,
+                  interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        try!(self.stream()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &mut Poll) -> io::Result<()> {
+        try!(self.stream()).deregister(poll)
+    }
+}
+
 /*
  *
  * ===== TcpListener =====
@@ -150,8 +279,14 @@ impl Evented for TcpStream {
 #[derive(Debug)]
 pub struct TcpListener {
     sys: sys::TcpListener,
+    // `TCP_NODELAY` value applied to accepted streams, see `ACCEPT_NODELAY_*`
+    accept_nodelay: AtomicUsize,
 }
 
+const ACCEPT_NODELAY_UNSET: usize = 0;
+const ACCEPT_NODELAY_OFF: usize = 1;
+const ACCEPT_NODELAY_ON: usize = 2;
+
 impl TcpListener {
     /// Convenience method to bind a new TCP listener to the specified address
     /// to receive new connections.
@@ -182,9 +317,7 @@ impl TcpListener {
 
         // listen
         let listener = try!(sock.listen(1024));
-        Ok(TcpListener {
-            sys: try!(sys::TcpListener::new(listener, addr)),
-        })
+        Ok(TcpListener::from_sys(try!(sys::TcpListener::new(listener, addr))))
     }
 
     /// Creates a new `TcpListener` from an instance of a
@@ -198,7 +331,7 @@ impl TcpListener {
     /// The address provided must be the address that the listener is bound to.
     pub fn from_listener(listener: net::TcpListener, addr: &SocketAddr)
                          -> io::Result<TcpListener> {
-        sys::TcpListener::new(listener, addr).map(|s| TcpListener { sys: s })
+        sys::TcpListener::new(listener, addr).map(TcpListener::from_sys)
     }
 
     /// Accepts a new `TcpStream`.
@@ -206,8 +339,56 @@ impl TcpListener {
     /// Returns a `Ok(None)` when the socket `WOULDBLOCK`, this means the stream
     /// will be ready at a later point. If an accepted stream is returned, the
     /// address of the peer is returned along with it
+    ///
+    /// Accepted streams are always in nonblocking mode. If a value was set
+    /// with `set_accept_nodelay`, it is applied to them as well.
     pub fn accept(&self) -> io::Result<Option<(TcpStream, SocketAddr)>> {
-        self.sys.accept().map(|o| o.map(|(s, a)| (TcpStream { sys: s }, a)))
+        let conn = match try!(self.sys.accept()) {
+            Some((s, a)) => (TcpStream { sys: s }, a),
+            None => return Ok(None),
+        };
+
+        match self.accept_nodelay.load(Ordering::Relaxed) {
+            ACCEPT_NODELAY_OFF => try!(conn.0.set_nodelay(false)),
+            ACCEPT_NODELAY_ON => try!(conn.0.set_nodelay(true)),
+            _ => {}
+        }
+
+        Ok(Some(conn))
+    }
+
+    /// Sets the value of `TCP_NODELAY` for every stream accepted from now on.
+    ///
+    /// Whether accepted streams inherit the option from the listener varies
+    /// across platforms, this makes it consistent. By default the option is
+    /// left as the OS sets it.
+    pub fn set_accept_nodelay(&self, nodelay: bool) {
+        let val = if nodelay { ACCEPT_NODELAY_ON } else { ACCEPT_NODELAY_OFF };
+        self.accept_nodelay.store(val, Ordering::Relaxed);
+    }
+
+    /// Accepts connections until none are pending, appending them to `out`.
+    ///
+    /// With edge-triggered registrations a readable event is only reported
+    /// once for any number of pending connections, so they must all be
+    /// accepted before waiting for the next event. Returns the number of
+    /// connections accepted. If an error occurs, the connections accepted
+    /// before it are still in `out`.
+    ///
+    /// When the process runs out of file descriptors the pending connection
+    /// stays queued, so the listener remains readable and retrying right
+    /// away fails again. Such errors can be told apart with
+    /// `is_fd_exhaustion`, and the usual remedy is to stop accepting until
+    /// some connections have been closed.
+    pub fn accept_all(&self, out: &mut Vec<(TcpStream, SocketAddr)>) -> io::Result<usize> {
+        let mut n = 0;
+
+        while let Some(conn) = try!(self.accept()) {
+            out.push(conn);
+            n += 1;
+        }
+
+        Ok(n)
     }
 
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
@@ -215,12 +396,50 @@ impl TcpListener {
     }
 
     pub fn try_clone(&self) -> io::Result<TcpListener> {
-        self.sys.try_clone().map(|s| TcpListener { sys: s })
+        self.sys.try_clone().map(|s| {
+            TcpListener {
+                sys: s,
+                accept_nodelay: AtomicUsize::new(self.accept_nodelay.load(Ordering::Relaxed)),
+            }
+        })
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.sys.set_ttl(ttl)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.sys.ttl()
+    }
+
+    /// Sets the value for the `IPV6_V6ONLY` option on this socket.
+    ///
+    /// Most platforms only honor this option before the socket is bound, and
+    /// `TcpListener::bind` binds immediately. To accept IPv4-mapped
+    /// connections on a listener bound to `::`, configure the socket with
+    /// `net2::TcpBuilder::only_v6` before binding and transfer it into mio with
+    /// `TcpListener::from_listener`.
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        self.sys.set_only_v6(only_v6)
+    }
+
+    /// Gets the value of the `IPV6_V6ONLY` option for this socket.
+    pub fn only_v6(&self) -> io::Result<bool> {
+        self.sys.only_v6()
     }
 
     pub fn take_socket_error(&self) -> io::Result<()> {
         self.sys.take_socket_error()
     }
+
+    fn from_sys(sys: sys::TcpListener) -> TcpListener {
+        TcpListener {
+            sys: sys,
+            accept_nodelay: AtomicUsize::new(ACCEPT_NODELAY_UNSET),
+        }
+    }
 }
 
 impl Evented for TcpListener {
@@ -294,6 +513,6 @@ impl AsRawFd for TcpListener {
 #[cfg(unix)]
 impl FromRawFd for TcpListener {
     unsafe fn from_raw_fd(fd: RawFd) -> TcpListener {
-        TcpListener { sys: FromRawFd::from_raw_fd(fd) }
+        TcpListener::from_sys(FromRawFd::from_raw_fd(fd))
     }
 }
@@ -55,6 +55,23 @@ impl UdpSocket {
         self.sys.recv_from(buf)
     }
 
+    /// Receives a datagram into a newly allocated buffer of up to `max`
+    /// bytes, which is truncated to the size of the datagram.
+    ///
+    /// Datagrams larger than `max` are handled as by `recv_from`.
+    pub fn recv_from_vec(&self, max: usize)
+                         -> io::Result<Option<(Vec<u8>, SocketAddr)>> {
+        let mut buf = vec![0; max];
+
+        match try!(self.recv_from(&mut buf)) {
+            Some((cnt, addr)) => {
+                buf.truncate(cnt);
+                Ok(Some((buf, addr)))
+            }
+            None => Ok(None),
+        }
+    }
+
     pub fn set_broadcast(&self, on: bool) -> io::Result<()> {
         self.sys.set_broadcast(on)
     }
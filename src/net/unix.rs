@@ -221,6 +221,11 @@ impl From<sys::UnixSocket> for UnixListener {
  *
  */
 
+/// Creates a new non-blocking OS pipe, returning its two ends.
+///
+/// Both ends implement `Evented`, so they can be registered with a `Poll`
+/// like any socket, for instance as a self-pipe. Reading from an empty pipe
+/// or writing to a full one returns a `WouldBlock` error.
 pub fn pipe() -> io::Result<(PipeReader, PipeWriter)> {
     let (rd, wr) = try!(sys::pipe());
     Ok((From::from(rd), From::from(wr)))
@@ -2,7 +2,7 @@ use {sys, Evented, EventSet, Poll, PollOpt, Token};
 use util::BoundedQueue;
 use std::{fmt, cmp, io, error, any};
 use std::sync::Arc;
-use std::sync::atomic::AtomicIsize;
+use std::sync::atomic::{AtomicIsize, AtomicUsize};
 use std::sync::atomic::Ordering::Relaxed;
 
 const SLEEP: isize = -1;
@@ -35,6 +35,16 @@ impl<M> Notify<M> {
         self.inner.notify(value)
     }
 
+    #[inline]
+    pub fn notify_counted(&self, value: M) -> Result<usize, NotifyError<M>> {
+        self.inner.notify_counted(value)
+    }
+
+    #[inline]
+    pub fn notify_all<I: Iterator<Item=M>>(&self, iter: I) -> Result<usize, (usize, NotifyError<M>)> {
+        self.inner.notify_all(iter)
+    }
+
     #[inline]
     pub fn poll(&self) -> Option<M> {
         self.inner.poll()
@@ -49,6 +59,21 @@ impl<M> Notify<M> {
     pub fn close(&self) {
         self.inner.close();
     }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    #[inline]
+    pub fn dropped_count(&self) -> usize {
+        self.inner.dropped.load(Relaxed)
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.inner.queue.capacity()
+    }
 }
 
 impl<M> Clone for Notify<M> {
@@ -65,11 +90,15 @@ impl<M> fmt::Debug for Notify<M> {
     }
 }
 
+// Messages are only ever moved through the queue, so sharing a `Notify`
+// between threads is safe as long as the messages can be sent between them
 unsafe impl<M: Send> Sync for Notify<M> { }
 unsafe impl<M: Send> Send for Notify<M> { }
 
 struct NotifyInner<M> {
     state: AtomicIsize,
+    // Number of messages refused because the queue was full
+    dropped: AtomicUsize,
     queue: BoundedQueue<M>,
     awaken: sys::Awakener
 }
@@ -78,6 +107,7 @@ impl<M> NotifyInner<M> {
     fn with_capacity(capacity: usize) -> io::Result<NotifyInner<M>> {
         Ok(NotifyInner {
             state: AtomicIsize::new(0),
+            dropped: AtomicUsize::new(0),
             queue: BoundedQueue::with_capacity(capacity),
             awaken: try!(sys::Awakener::new())
         })
@@ -129,7 +159,12 @@ impl<M> NotifyInner<M> {
     }
 
     fn notify(&self, value: M) -> Result<(), NotifyError<M>> {
-        let mut cur = self.state.load(Relaxed);
+        self.notify_counted(value).map(|_| ())
+    }
+
+    // Returns the number of messages pending once this one was added
+    fn notify_counted(&self, value: M) -> Result<usize, NotifyError<M>> {
+        let cur = self.state.load(Relaxed);
 
         if cur == CLOSED {
             // The receiving end has already hung up
@@ -138,22 +173,72 @@ impl<M> NotifyInner<M> {
 
         // First, push the message onto the queue
         if let Err(value) = self.queue.push(value) {
+            self.dropped.fetch_add(1, Relaxed);
             return Err(NotifyError::Full(value));
         }
 
+        self.add(cur, 1)
+    }
+
+    fn notify_all<I: Iterator<Item=M>>(&self, mut iter: I) -> Result<usize, (usize, NotifyError<M>)> {
+        let cur = self.state.load(Relaxed);
+
+        if cur == CLOSED {
+            // The receiving end has already hung up
+            return Err((0, NotifyError::Closed(iter.next())));
+        }
+
+        let mut n = 0;
+        let mut full = None;
+
+        // Push as many messages as fit, then account for all of them at once
+        for value in iter {
+            if let Err(value) = self.queue.push(value) {
+                self.dropped.fetch_add(1, Relaxed);
+                full = Some(value);
+                break;
+            }
+
+            n += 1;
+        }
+
+        if n > 0 {
+            if let Err(e) = self.add(cur, n) {
+                let sent = match e {
+                    NotifyError::Closed(..) => 0,
+                    _ => n,
+                };
+
+                return Err((sent, e));
+            }
+        }
+
+        match full {
+            Some(value) => Err((n, NotifyError::Full(value))),
+            None => Ok(n),
+        }
+    }
+
+    // Accounts for `n` messages that were pushed onto the queue, waking up the
+    // event loop if it is sleeping. Returns the resulting number of pending
+    // messages.
+    fn add(&self, mut cur: isize, n: usize) -> Result<usize, NotifyError<M>> {
+        let n = n as isize;
         let mut nxt;
         let mut val;
 
         loop {
             nxt = match cur {
                 CLOSED => {
-                    // The receiving end has hung up, and we cannot reliably get our message back
-                    // We poll 1 message from the queue to make sure that no message is stuck
-                    let _ = self.queue.pop();
+                    // The receiving end has hung up, and we cannot reliably get our messages back
+                    // We poll as many messages from the queue to make sure that none are stuck
+                    for _ in 0..n {
+                        let _ = self.queue.pop();
+                    }
                     return Err(NotifyError::Closed(None));
                 }
-                SLEEP => { 1 }
-                _ => { cur + 1 }
+                SLEEP => { n }
+                _ => { cur + n }
             };
 
             val = self.state.compare_and_swap(cur, nxt, Relaxed);
@@ -171,7 +256,7 @@ impl<M> NotifyInner<M> {
             }
         }
 
-        Ok(())
+        Ok(nxt as usize)
     }
 
     fn close(&self) {
@@ -181,6 +266,10 @@ impl<M> NotifyInner<M> {
         }
     }
 
+    fn is_closed(&self) -> bool {
+        self.state.load(Relaxed) == CLOSED
+    }
+
     fn cleanup(&self) {
         self.awaken.cleanup();
     }
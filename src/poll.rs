@@ -1,7 +1,13 @@
 use {convert, sys, Evented, Token};
 use event::{EventSet, Event, PollOpt};
-use std::{fmt, io};
+use std::{any, cmp, fmt, io, mem};
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
+use timer::Timer;
+
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
 
 /// The `Poll` type acts as an interface allowing a program to wait on a set of
 /// IO handles until one or more become "ready" to be operated on. An IO handle
@@ -19,6 +25,20 @@ use std::time::Duration;
 /// level-triggered notifications. This is done by specifying the `PollOpt`
 /// argument to `register()` and `reregister()`.
 ///
+/// ## Coalescing
+///
+/// Each registered handle is reported at most once per call to `poll`. When a
+/// handle became ready for several operations, for instance both readable
+/// and writable, a single `Event` carrying all of them is returned.
+///
+/// ## Fairness
+///
+/// The OS tends to report ready handles in the same order on every call, so
+/// under heavy load the handles processed last may be starved. With
+/// `set_fairness(true)` the starting point of `events()` is rotated by one on
+/// each call to `poll`, so that every handle periodically gets to be first.
+/// This only affects the order in which the events are returned.
+///
 /// ## Portability
 ///
 /// Cross platform portability is provided for Mio's TCP & UDP implementations.
@@ -44,19 +64,124 @@ use std::time::Duration;
 pub struct Poll {
     selector: sys::Selector,
     events: sys::Events,
+    capacity: usize,
+    // Capacity of `events`, which is smaller than `capacity` after a call to
+    // `poll_budget`, and the other buffer along with its capacity
+    events_cap: usize,
+    spare: Option<(usize, sys::Events)>,
+    fair: bool,
+    rotation: usize,
+    start: usize,
+    had_more: bool,
+    // Buffer used by `poll_one`, and the position of the next event in it
+    one: sys::Events,
+    one_pos: usize,
+    // Drives the tick set with `set_tick`, its only entry is the tick token
+    tick: Option<Timer<Token>>,
+    // The tick token, if the last call to `poll` reported it
+    ticked: Option<Token>,
+    labels: HashMap<Token, Label>,
+    // Shared by the handles returned by `register_owned` to deregister
+    // themselves, created on first use
+    #[cfg(unix)]
+    owned: Option<Arc<sys::Selector>>,
+    #[cfg(feature = "timestamp")]
+    polled_at: u64,
 }
 
+/// A value attached to a token with `Poll::set_label`.
+pub type Label = Arc<any::Any + Send + Sync>;
+
 impl Poll {
     pub fn new() -> io::Result<Poll> {
-        Ok(Poll {
-            selector: try!(sys::Selector::new()),
-            events: sys::Events::new(),
-        })
+        Poll::with_capacity(1024)
+    }
+
+    /// Creates a new `Poll` that returns at most `capacity` events from each
+    /// call to `poll`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize) -> io::Result<Poll> {
+        assert!(capacity > 0, "capacity must not be zero");
+        Ok(Poll::from_selector(try!(sys::Selector::new()), capacity))
     }
 
+    fn from_selector(selector: sys::Selector, capacity: usize) -> Poll {
+        Poll {
+            selector: selector,
+            events: sys::Events::with_capacity(capacity),
+            capacity: capacity,
+            events_cap: capacity,
+            spare: None,
+            fair: false,
+            rotation: 0,
+            start: 0,
+            had_more: false,
+            one: sys::Events::with_capacity(1),
+            one_pos: 0,
+            tick: None,
+            ticked: None,
+            labels: HashMap::new(),
+            #[cfg(unix)]
+            owned: None,
+            #[cfg(feature = "timestamp")]
+            polled_at: 0,
+        }
+    }
+
+    /// Creates a new `Poll` instance referring to the same underlying OS
+    /// selector.
+    ///
+    /// IO handles registered through either instance are polled by both.
+    /// Each instance has its own events buffer; when several threads call
+    /// `poll` concurrently, a given readiness event is delivered to only one
+    /// of them and which one is unspecified. Edge-triggered handles should
+    /// generally be polled from a single instance.
+    pub fn try_clone(&self) -> io::Result<Poll> {
+        let mut poll = Poll::from_selector(try!(self.selector.try_clone()), self.capacity);
+        poll.fair = self.fair;
+        Ok(poll)
+    }
+
+    /// Pins the calling thread to the CPU with index `cpu`.
+    ///
+    /// This is a performance knob for thread-per-core designs running one
+    /// `Poll` per core, and should be called from the thread that drives this
+    /// `Poll`. It only affects the calling thread. Only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn set_cpu_affinity(&self, cpu: usize) -> io::Result<()> {
+        sys::set_cpu_affinity(cpu)
+    }
+
+    /// Enables or disables rotating the order of returned events across
+    /// calls to `poll`. Disabled by default.
+    pub fn set_fairness(&mut self, fair: bool) {
+        self.fair = fair;
+    }
+
+    /// Registers an IO handle with the `Poll` instance.
+    ///
+    /// Returns an `InvalidInput` error if `interest` is empty, as such a
+    /// handle would never be reported as ready. To temporarily silence a
+    /// handle that is already registered, `reregister` it with
+    /// `EventSet::none()` instead.
+    ///
+    /// A handle may only be registered once per `Poll`, use `reregister` to
+    /// change its token or interest. Depending on the platform, registering
+    /// it a second time either fails or replaces the first registration.
+    /// On Unix, the same socket can be watched under several tokens by
+    /// registering a `try_clone` of it under each one. The duplicates are
+    /// tracked independently by the OS, so deregistering one of them leaves
+    /// the others in place.
     pub fn register<E: ?Sized>(&mut self, io: &E, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()>
         where E: Evented
     {
+        if interest.is_none() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "interest must not be empty"));
+        }
+
         trace!("registering with poller");
 
         // Register interests for this socket
@@ -65,6 +190,28 @@ impl Poll {
         Ok(())
     }
 
+    /// Registers an IO handle like `register`, taking ownership of it.
+    ///
+    /// The returned `Registered` deregisters the handle when it is dropped,
+    /// so it can't be forgotten. All the handles registered through a `Poll`
+    /// share one extra reference to the underlying selector, which costs a
+    /// single file descriptor. Only available on Unix.
+    #[cfg(unix)]
+    pub fn register_owned<E>(&mut self, io: E, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<Registered<E>>
+        where E: Evented + AsRawFd
+    {
+        if self.owned.is_none() {
+            self.owned = Some(Arc::new(try!(self.selector.try_clone())));
+        }
+
+        try!(self.register(&io, token, interest, opts));
+
+        Ok(Registered {
+            selector: self.owned.as_ref().unwrap().clone(),
+            io: Some(io),
+        })
+    }
+
     pub fn reregister<E: ?Sized>(&mut self, io: &E, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()>
         where E: Evented
     {
@@ -76,6 +223,34 @@ impl Poll {
         Ok(())
     }
 
+    /// Reregisters several IO handles at once.
+    ///
+    /// This is equivalent to calling `reregister` for each entry, but on
+    /// kqueue platforms all the changes are submitted with a single system
+    /// call. epoll requires a system call per handle regardless. If a change
+    /// fails, the changes submitted along with it may or may not have been
+    /// applied.
+    pub fn reregister_batch(&mut self, changes: &[(&Evented, Token, EventSet, PollOpt)]) -> io::Result<()> {
+        trace!("registering batch with poller; len={}", changes.len());
+
+        self.selector.begin_batch();
+
+        for &(io, token, interest, opts) in changes {
+            if let Err(e) = io.reregister(self, token, interest, opts) {
+                // Still submit the entries that were already queued
+                let _ = self.selector.end_batch();
+                return Err(e);
+            }
+        }
+
+        self.selector.end_batch()
+    }
+
+    /// Deregisters an IO handle from the `Poll` instance.
+    ///
+    /// Handles should be deregistered before they are closed. Closing the
+    /// underlying fd first already removes it from the OS selector, so
+    /// deregistering it afterwards succeeds without doing anything.
     pub fn deregister<E: ?Sized>(&mut self, io: &E) -> io::Result<()>
         where E: Evented
     {
@@ -87,10 +262,226 @@ impl Poll {
         Ok(())
     }
 
+    /// Waits for readiness events, blocking for at most `timeout`.
+    ///
+    /// The precision of the timeout depends on the platform. kqueue honors
+    /// the full `Duration`, as does epoll on Linux kernels providing
+    /// `epoll_pwait2` (5.11 and newer). Elsewhere the timeout is rounded up to
+    /// the next millisecond, so the call never returns before `timeout` has
+    /// elapsed unless an event is received.
+    ///
+    /// A timeout of zero returns immediately while `None` blocks until an
+    /// event is received. Timeouts larger than the platform can represent
+    /// are clamped to that maximum, which may be as low as `i32::MAX`
+    /// milliseconds (about 24 days).
     pub fn poll(&mut self, timeout: Option<Duration>) -> io::Result<usize> {
-        let timeout = timeout.map(|to| convert::millis(to) as usize);
+        let capacity = self.capacity;
+        self.poll_capped(timeout, capacity)
+    }
+
+    /// Waits for events like `poll`, but returns at most `max_events` of
+    /// them.
+    ///
+    /// Only `max_events` events are requested from the OS, and the others
+    /// remain queued there for subsequent calls. This bounds the work done
+    /// by each iteration of a loop whose handlers are expensive, keeping it
+    /// responsive. `had_more` tells whether the budget was used up. A budget
+    /// larger than the capacity of the `Poll` is the same as calling `poll`.
+    ///
+    /// On Windows, events deferred by previous calls may be returned in
+    /// excess of the budget, and the tick set with `set_tick` is never
+    /// counted against it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_events` is zero.
+    pub fn poll_budget(&mut self, timeout: Option<Duration>, max_events: usize) -> io::Result<usize> {
+        assert!(max_events > 0, "event budget must not be zero");
+
+        let capacity = cmp::min(max_events, self.capacity);
+        self.poll_capped(timeout, capacity)
+    }
+
+    fn poll_capped(&mut self, timeout: Option<Duration>, capacity: usize) -> io::Result<usize> {
+        let timeout = self.tick_timeout(timeout);
+
+        self.resize_events(capacity);
+
         try!(self.selector.select(&mut self.events, timeout));
-        Ok(self.events.len())
+
+        self.had_more = self.events.is_full();
+        self.stamp();
+        self.ticked = self.advance_tick();
+
+        let len = self.events.len();
+
+        if self.fair && len > 0 {
+            self.start = self.rotation % len;
+            self.rotation = self.rotation.wrapping_add(1);
+        } else {
+            self.start = 0;
+        }
+
+        Ok(self.len())
+    }
+
+    /// Makes `poll` report a readable event for `token` every `period`.
+    ///
+    /// This is a heartbeat for loops that need to run periodic work without
+    /// setting up a timer. The first tick is reported `period` from now.
+    /// `poll` returns early as needed to report ticks on time, and if it
+    /// falls behind, missed ticks are skipped rather than reported in a
+    /// burst. The tick event always comes after the events received from the
+    /// OS, and is not reported by `poll_one`. Calling `set_tick` again
+    /// replaces the previous tick. The period is rounded up to a whole number
+    /// of milliseconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn set_tick(&mut self, token: Token, period: Duration) {
+        let period = convert::millis_ceil(period);
+
+        assert!(period > 0, "tick period must not be zero");
+
+        // One wheel tick per period, so that `poll` only wakes up for ticks
+        let mut timer = Timer::new(period, 1, 1);
+        timer.setup();
+        // The timer has room for exactly this entry
+        timer.interval_ms(token, period).unwrap();
+
+        self.tick = Some(timer);
+    }
+
+    /// Attaches `label` to `token`, replacing any previous label.
+    ///
+    /// Labels are only a diagnostic aid, for instance to find out which
+    /// handle an unexpected event belongs to without maintaining a separate
+    /// map. `Poll` doesn't know which handle a token was registered for, so
+    /// labels are not removed on `deregister`, use `remove_label` instead.
+    /// Labels are not shared with the instances created by `try_clone`.
+    pub fn set_label(&mut self, token: Token, label: Label) {
+        self.labels.insert(token, label);
+    }
+
+    /// Returns the label attached to `token` with `set_label`.
+    pub fn label(&self, token: Token) -> Option<Label> {
+        self.labels.get(&token).map(|l| l.clone())
+    }
+
+    pub fn remove_label(&mut self, token: Token) -> Option<Label> {
+        self.labels.remove(&token)
+    }
+
+    /// Stops the tick set with `set_tick`.
+    pub fn clear_tick(&mut self) {
+        self.tick = None;
+        self.ticked = None;
+    }
+
+    /// Waits for events like `poll` and returns an iterator over them.
+    ///
+    /// The events live in a buffer owned by the `Poll` which is reused, so
+    /// the returned `Events` borrows the `Poll` and must be dropped before
+    /// polling again.
+    pub fn poll_iter(&mut self, timeout: Option<Duration>) -> io::Result<Events> {
+        try!(self.poll(timeout));
+        Ok(self.events())
+    }
+
+    /// Waits for events like `poll` and calls `f` with each of them.
+    ///
+    /// Returns the number of events. The events are read straight from the
+    /// buffer filled by the OS, in the same order as `events()` would return
+    /// them.
+    pub fn poll_with<F>(&mut self, timeout: Option<Duration>, mut f: F) -> io::Result<usize>
+        where F: FnMut(Event)
+    {
+        let len = try!(self.poll(timeout));
+
+        for i in 0..len {
+            f(self.event(i).unwrap());
+        }
+
+        Ok(len)
+    }
+
+    /// Waits for a single event, blocking for at most `timeout`.
+    ///
+    /// Only one event is requested from the OS, and the others remain
+    /// queued there for subsequent calls. This minimizes the time between a
+    /// handle becoming ready and it being handled, at the cost of a system
+    /// call per event, so `poll` gives better throughput when many handles
+    /// are ready at once. Returns `None` if the timeout elapsed.
+    ///
+    /// The event is not part of `events()`, which still refers to the batch
+    /// returned by the last call to `poll`.
+    pub fn poll_one(&mut self, timeout: Option<Duration>) -> io::Result<Option<Event>> {
+        // Some selectors can return more events than requested
+        if self.one_pos >= self.one.len() {
+            try!(self.selector.select(&mut self.one, timeout));
+            self.stamp();
+            self.one_pos = 0;
+        }
+
+        let event = self.one.get(self.one_pos).map(|e| self.stamped(e));
+
+        if event.is_some() {
+            self.one_pos += 1;
+        }
+
+        Ok(event)
+    }
+
+    /// Returns `true` if the last call to `poll` filled the events buffer.
+    ///
+    /// In that case the OS may have more events ready than could be
+    /// returned, and they can be retrieved right away with `poll_now`
+    /// instead of waiting for the next timeout.
+    pub fn had_more(&self) -> bool {
+        self.had_more
+    }
+
+    /// Polls for events that are ready right now without blocking.
+    ///
+    /// This is equivalent to calling `poll` with a zero timeout. Note that
+    /// passing `None` to `poll` blocks indefinitely, which is easy to confuse
+    /// with "don't wait".
+    pub fn poll_now(&mut self) -> io::Result<usize> {
+        self.poll(Some(Duration::from_millis(0)))
+    }
+
+    /// Removes any events for `token` from the most recent batch of events.
+    ///
+    /// Deregistering a handle does not affect events that have already been
+    /// returned by `poll`. If a handle is deregistered (and perhaps its token
+    /// reused) while the batch is still being processed, the stale events can
+    /// be purged so that they are not dispatched:
+    ///
+    /// ```no_run
+    /// # use mio::*;
+    /// # use mio::tcp::*;
+    /// # let mut poll = Poll::new().unwrap();
+    /// # let stream = TcpStream::connect(&"127.0.0.1:80".parse().unwrap()).unwrap();
+    /// poll.deregister(&stream).unwrap();
+    /// poll.remove_token(Token(0));
+    /// ```
+    pub fn remove_token(&mut self, token: Token) {
+        self.events.remove_token(token);
+
+        if self.ticked == Some(token) {
+            self.ticked = None;
+        }
+    }
+
+    /// Sorts the most recent batch of events by token.
+    ///
+    /// The sort is stable. The order in which the OS reports events is
+    /// unspecified, so this is mostly useful for deterministic processing in
+    /// tests. It does not affect subsequent calls to `poll`.
+    pub fn sort_by_token(&mut self) {
+        self.events.sort_by_token();
+        self.start = 0;
     }
 
     pub fn events(&self) -> Events {
@@ -99,6 +490,92 @@ impl Poll {
             poll: self,
         }
     }
+
+    // Makes `events` a buffer for `capacity` events, keeping the previous
+    // buffer around so that alternating with `poll_budget` doesn't allocate
+    fn resize_events(&mut self, capacity: usize) {
+        if self.events_cap == capacity {
+            return;
+        }
+
+        let events = match self.spare.take() {
+            Some((cap, events)) => {
+                if cap == capacity {
+                    events
+                } else {
+                    sys::Events::with_capacity(capacity)
+                }
+            }
+            None => sys::Events::with_capacity(capacity),
+        };
+
+        let prev = mem::replace(&mut self.events, events);
+        self.spare = Some((self.events_cap, prev));
+        self.events_cap = capacity;
+    }
+
+    // Number of events in the last batch, including the tick
+    fn len(&self) -> usize {
+        self.events.len() + if self.ticked.is_some() { 1 } else { 0 }
+    }
+
+    fn event(&self, idx: usize) -> Option<Event> {
+        let len = self.events.len();
+
+        if idx >= len {
+            if idx == len {
+                if let Some(token) = self.ticked {
+                    return Some(self.stamped(Event::new(EventSet::readable(), token)));
+                }
+            }
+
+            return None;
+        }
+
+        self.events.get((self.start + idx) % len).map(|e| self.stamped(e))
+    }
+
+    // Shortens `timeout` so that `poll` returns in time for the next tick
+    fn tick_timeout(&self, timeout: Option<Duration>) -> Option<Duration> {
+        let rem = match self.tick.as_ref().and_then(|timer| timer.next_tick_in_ms()) {
+            Some(ms) => Duration::from_millis(ms),
+            None => return timeout,
+        };
+
+        match timeout {
+            Some(to) => Some(cmp::min(to, rem)),
+            None => Some(rem),
+        }
+    }
+
+    // Returns the tick token if a tick is due. The timer re-arms the tick,
+    // skipping the periods missed if `poll` fell behind.
+    fn advance_tick(&mut self) -> Option<Token> {
+        self.tick.as_mut().and_then(|timer| {
+            let now = timer.now();
+            timer.tick_to(now)
+        })
+    }
+
+    #[cfg(feature = "timestamp")]
+    fn stamp(&mut self) {
+        self.polled_at = ::time::precise_time_ns();
+    }
+
+    #[cfg(not(feature = "timestamp"))]
+    fn stamp(&mut self) {
+    }
+
+    #[cfg(feature = "timestamp")]
+    fn stamped(&self, mut event: Event) -> Event {
+        ::event::set_timestamp(&mut event, self.polled_at);
+        event
+    }
+
+    #[cfg(not(feature = "timestamp"))]
+    fn stamped(&self, event: Event) -> Event {
+        event
+    }
 }
 
 impl fmt::Debug for Poll {
@@ -107,6 +584,50 @@ impl fmt::Debug for Poll {
     }
 }
 
+/// An IO handle owned by its registration, see `Poll::register_owned`.
+///
+/// The handle is deregistered when the `Registered` is dropped.
+#[cfg(unix)]
+pub struct Registered<E: Evented + AsRawFd> {
+    selector: Arc<sys::Selector>,
+    // Only `None` once `into_inner` has taken the handle
+    io: Option<E>,
+}
+
+#[cfg(unix)]
+impl<E: Evented + AsRawFd> Registered<E> {
+    pub fn get_ref(&self) -> &E {
+        self.io.as_ref().unwrap()
+    }
+
+    pub fn get_mut(&mut self) -> &mut E {
+        self.io.as_mut().unwrap()
+    }
+
+    /// Deregisters the handle and returns it.
+    pub fn into_inner(mut self) -> io::Result<E> {
+        let io = self.io.take().unwrap();
+        try!(self.selector.remove(io.as_raw_fd()));
+        Ok(io)
+    }
+}
+
+#[cfg(unix)]
+impl<E: Evented + AsRawFd> Drop for Registered<E> {
+    fn drop(&mut self) {
+        if let Some(ref io) = self.io {
+            let _ = self.selector.remove(io.as_raw_fd());
+        }
+    }
+}
+
+#[cfg(unix)]
+impl<E: Evented + AsRawFd + fmt::Debug> fmt::Debug for Registered<E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Registered {{ io: {:?} }}", self.io)
+    }
+}
+
 pub struct Events<'a> {
     curr: usize,
     poll: &'a Poll,
@@ -114,15 +635,15 @@ pub struct Events<'a> {
 
 impl<'a> Events<'a> {
     pub fn get(&self, idx: usize) -> Option<Event> {
-        self.poll.events.get(idx)
+        self.poll.event(idx)
     }
 
     pub fn len(&self) -> usize {
-        self.poll.events.len()
+        self.poll.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.poll.events.is_empty()
+        self.poll.len() == 0
     }
 }
 
@@ -130,11 +651,11 @@ impl<'a> Iterator for Events<'a> {
     type Item = Event;
 
     fn next(&mut self) -> Option<Event> {
-        if self.curr == self.poll.events.len() {
+        if self.curr == self.poll.len() {
             return None;
         }
 
-        let ret = self.poll.events.get(self.curr).unwrap();
+        let ret = self.poll.event(self.curr).unwrap();
         self.curr += 1;
         Some(ret)
     }
@@ -9,10 +9,14 @@ pub use self::unix::{
     TcpListener,
     UdpSocket,
     UnixSocket,
+    is_fd_exhaustion,
     pipe,
     set_nonblock,
 };
 
+#[cfg(target_os = "linux")]
+pub use self::unix::set_cpu_affinity;
+
 
 #[cfg(unix)]
 mod unix;
@@ -25,6 +29,7 @@ pub use self::windows::{
     TcpStream,
     TcpListener,
     UdpSocket,
+    is_fd_exhaustion,
 };
 
 #[cfg(windows)]
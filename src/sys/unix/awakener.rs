@@ -1,6 +1,11 @@
+#[cfg(not(any(target_os = "freebsd", target_os = "ios", target_os = "macos")))]
 pub use self::pipe::Awakener;
 
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+pub use self::user::Awakener;
+
 /// Default *nix awakener implementation
+#[cfg(not(any(target_os = "freebsd", target_os = "ios", target_os = "macos")))]
 mod pipe {
     use {io, Evented, EventSet, Poll, PollOpt, Token, TryRead, TryWrite};
     use unix::{self, PipeReader, PipeWriter};
@@ -61,3 +66,92 @@ mod pipe {
         }
     }
 }
+
+/// Awakener backed by an `EVFILT_USER` kqueue event, which avoids the pipe
+/// and the syscalls needed to drain it
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+mod user {
+    use {io, poll, Evented, EventSet, Poll, PollOpt, Token};
+    use sys::unix::kqueue;
+    use nix::unistd::close;
+    use std::os::unix::io::RawFd;
+    use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+
+    // User events are identified by an integer rather than an fd, each
+    // awakener gets its own
+    static NEXT_IDENT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    const UNREGISTERED: isize = -1;
+
+    /*
+     *
+     * ===== Awakener =====
+     *
+     */
+
+    pub struct Awakener {
+        ident: usize,
+        // Duplicate of the kqueue fd the event was added to
+        kq: AtomicIsize,
+    }
+
+    impl Awakener {
+        pub fn new() -> io::Result<Awakener> {
+            Ok(Awakener {
+                ident: NEXT_IDENT.fetch_add(1, Ordering::Relaxed),
+                kq: AtomicIsize::new(UNREGISTERED),
+            })
+        }
+
+        pub fn wakeup(&self) -> io::Result<()> {
+            let kq = self.kq.load(Ordering::Acquire);
+
+            // Until it is registered, there is no poller to wake up
+            if kq == UNREGISTERED {
+                return Ok(());
+            }
+
+            kqueue::trigger_user(kq as RawFd, self.ident)
+        }
+
+        pub fn cleanup(&self) {
+            // The event is registered with EV_CLEAR, there is nothing to
+            // consume
+        }
+    }
+
+    impl Evented for Awakener {
+        fn register(&self, poll: &mut Poll, token: Token, _: EventSet, _: PollOpt) -> io::Result<()> {
+            let selector = poll::selector_mut(poll);
+
+            try!(selector.register_user(self.ident, token));
+
+            let kq = try!(selector.dup_fd());
+
+            if self.kq.compare_and_swap(UNREGISTERED, kq as isize, Ordering::Release) != UNREGISTERED {
+                let _ = close(kq);
+                return Err(io::Error::new(io::ErrorKind::Other, "awakener is already registered"));
+            }
+
+            Ok(())
+        }
+
+        fn reregister(&self, _: &mut Poll, _: Token, _: EventSet, _: PollOpt) -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::Other, "awakener cannot be reregistered"))
+        }
+
+        fn deregister(&self, _: &mut Poll) -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::Other, "awakener cannot be deregistered"))
+        }
+    }
+
+    impl Drop for Awakener {
+        fn drop(&mut self) {
+            let kq = self.kq.load(Ordering::Acquire);
+
+            if kq != UNREGISTERED {
+                let _ = close(kq as RawFd);
+            }
+        }
+    }
+}
@@ -1,9 +1,10 @@
-use {io, EventSet, PollOpt, Token};
+use {convert, io, EventSet, PollOpt, Token};
 use event::Event;
 use nix::sys::epoll::*;
-use nix::unistd::close;
+use nix::unistd::{close, dup};
 use std::os::unix::io::RawFd;
 use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::time::Duration;
 
 /// Each Selector has a globally unique(ish) ID associated with it. This ID
 /// gets tracked by `TcpStream`, `TcpListener`, etc... when they are first
@@ -15,17 +16,20 @@ static NEXT_ID: AtomicUsize = ATOMIC_USIZE_INIT;
 #[derive(Debug)]
 pub struct Selector {
     id: usize,
-    epfd: RawFd
+    epfd: RawFd,
+    auto: super::AutoDeregister,
 }
 
 impl Selector {
     pub fn new() -> io::Result<Selector> {
-        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        // IDs start at one, so that zero can stand for "no selector"
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed) + 1;
         let epfd = try!(epoll_create().map_err(super::from_nix_error));
 
         Ok(Selector {
             id: id,
             epfd: epfd,
+            auto: super::AutoDeregister::new(),
         })
     }
 
@@ -33,27 +37,60 @@ impl Selector {
         self.id
     }
 
+    /// Duplicates the epoll fd. The clone keeps the same ID since it refers
+    /// to the same kernel object.
+    pub fn try_clone(&self) -> io::Result<Selector> {
+        let epfd = try!(dup(self.epfd).map_err(super::from_nix_error));
+
+        Ok(Selector {
+            id: self.id,
+            epfd: epfd,
+            auto: self.auto.clone(),
+        })
+    }
+
     /// Wait for events from the OS
-    pub fn select(&mut self, evts: &mut Events, timeout_ms: Option<usize>) -> io::Result<()> {
+    pub fn select(&mut self, evts: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
         use std::{cmp, i32, slice};
 
-        let timeout_ms = match timeout_ms {
-            None => -1 as i32,
-            Some(x) => cmp::min(i32::MAX as usize, x) as i32,
-        };
-
         let dst = unsafe {
             slice::from_raw_parts_mut(
                 evts.events.as_mut_ptr(),
                 evts.events.capacity())
         };
 
-        // Wait for epoll events for at most timeout_ms milliseconds
-        let cnt = try!(epoll_wait(self.epfd, dst, timeout_ms as isize)
-                           .map_err(super::from_nix_error));
+        // Prefer the nanosecond precision timeout when the kernel has it
+        let precise = match timeout {
+            Some(to) => epoll_pwait2(self.epfd, dst, to),
+            None => None,
+        };
+
+        let cnt = match precise {
+            Some(res) => try!(res),
+            None => {
+                let timeout_ms = match timeout {
+                    None => -1 as i32,
+                    Some(to) => cmp::min(i32::MAX as u64, convert::millis_ceil(to)) as i32,
+                };
+
+                // Wait for epoll events for at most timeout_ms milliseconds
+                try!(epoll_wait(self.epfd, dst, timeout_ms as isize)
+                         .map_err(super::from_nix_error))
+            }
+        };
 
         unsafe { evts.events.set_len(cnt); }
 
+        for e in &evts.events {
+            if let Some(fd) = self.auto.take(Token(e.data as usize)) {
+                // The events have been read already, so a failure must not
+                // lose them. The fd is disabled by EPOLLONESHOT regardless.
+                if let Err(err) = self.deregister(fd) {
+                    warn!("failed to auto deregister; fd={}; err={:?}", fd, err);
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -64,8 +101,24 @@ impl Selector {
             data: token.as_usize() as u64
         };
 
-        epoll_ctl(self.epfd, EpollOp::EpollCtlAdd, fd, &info)
-            .map_err(super::from_nix_error)
+        // Tracked before the fd is added, as a clone of the selector may
+        // receive its first event right away
+        let displaced = self.auto.update(fd, token, opts);
+
+        let res = if opts.is_exclusive() {
+            epoll_add_exclusive(self.epfd, fd, &info)
+        } else {
+            epoll_ctl(self.epfd, EpollOp::EpollCtlAdd, fd, &info)
+                .map_err(super::from_nix_error)
+        };
+
+        // A failed add must not touch tracking it didn't create: when the fd
+        // is already registered, `EEXIST` leaves that registration in place
+        if res.is_err() {
+            self.auto.restore(fd, token, displaced);
+        }
+
+        res
     }
 
     /// Register event interests for the given IO handle with the OS
@@ -75,12 +128,28 @@ impl Selector {
             data: token.as_usize() as u64
         };
 
-        epoll_ctl(self.epfd, EpollOp::EpollCtlMod, fd, &info)
-            .map_err(super::from_nix_error)
+        try!(epoll_ctl(self.epfd, EpollOp::EpollCtlMod, fd, &info)
+            .map_err(super::from_nix_error));
+
+        self.auto.update(fd, token, opts);
+        Ok(())
+    }
+
+    /// epoll takes a syscall per change, so batching is a no-op
+    pub fn begin_batch(&mut self) {
+    }
+
+    pub fn end_batch(&mut self) -> io::Result<()> {
+        Ok(())
     }
 
     /// Deregister event interests for the given IO handle with the OS
     pub fn deregister(&mut self, fd: RawFd) -> io::Result<()> {
+        self.remove(fd)
+    }
+
+    /// Deregisters `fd` like `deregister`, through a shared reference.
+    pub fn remove(&self, fd: RawFd) -> io::Result<()> {
         // The &info argument should be ignored by the system,
         // but linux < 2.6.9 required it to be not null.
         // For compatibility, we provide a dummy EpollEvent.
@@ -89,8 +158,92 @@ impl Selector {
             data: 0
         };
 
-        epoll_ctl(self.epfd, EpollOp::EpollCtlDel, fd, &info)
-            .map_err(super::from_nix_error)
+        self.auto.remove_fd(fd);
+
+        super::ignore_deregistered(
+            epoll_ctl(self.epfd, EpollOp::EpollCtlDel, fd, &info)
+                .map_err(super::from_nix_error))
+    }
+}
+
+/// Waits using a `timespec` timeout. Returns `None` if `epoll_pwait2` is not
+/// available, in which case the caller falls back to `epoll_wait`.
+///
+/// The syscall number differs between architectures, so this is limited to
+/// the ones libc knows it for.
+#[cfg(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64",
+    target_arch = "arm", target_arch = "aarch64", target_arch = "mips",
+    target_arch = "mips64", target_arch = "powerpc", target_arch = "powerpc64",
+    target_arch = "s390x", target_arch = "riscv64")))]
+fn epoll_pwait2(epfd: RawFd, dst: &mut [EpollEvent], timeout: Duration) -> Option<io::Result<usize>> {
+    use libc::{self, c_int, c_void, size_t, time_t, c_long, timespec};
+    use std::{cmp, i32, ptr};
+    use std::sync::atomic::{AtomicBool, ATOMIC_BOOL_INIT};
+
+    // Set once `epoll_pwait2` turns out to be unavailable, either because the
+    // kernel doesn't implement it or because a seccomp filter rejects it
+    static NO_PWAIT2: AtomicBool = ATOMIC_BOOL_INIT;
+
+    if NO_PWAIT2.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let ts = timespec {
+        tv_sec: cmp::min(timeout.as_secs(), i32::MAX as u64) as time_t,
+        tv_nsec: timeout.subsec_nanos() as c_long,
+    };
+
+    let res = unsafe {
+        libc::syscall(libc::SYS_epoll_pwait2,
+                      epfd,
+                      dst.as_mut_ptr(),
+                      dst.len() as c_int,
+                      &ts as *const timespec,
+                      ptr::null::<c_void>(),
+                      0 as size_t)
+    };
+
+    if res >= 0 {
+        return Some(Ok(res as usize));
+    }
+
+    let err = io::Error::last_os_error();
+
+    match err.raw_os_error() {
+        // Older container runtimes answer unknown syscalls with EPERM
+        Some(libc::ENOSYS) | Some(libc::EPERM) => {
+            NO_PWAIT2.store(true, Ordering::Relaxed);
+            None
+        }
+        _ => Some(Err(err)),
+    }
+}
+
+#[cfg(not(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64",
+    target_arch = "arm", target_arch = "aarch64", target_arch = "mips",
+    target_arch = "mips64", target_arch = "powerpc", target_arch = "powerpc64",
+    target_arch = "s390x", target_arch = "riscv64"))))]
+fn epoll_pwait2(_: RawFd, _: &mut [EpollEvent], _: Duration) -> Option<io::Result<usize>> {
+    None
+}
+
+/// Not known to nix, so exclusive registrations go through libc directly.
+const EPOLLEXCLUSIVE: u32 = 1 << 28;
+
+fn epoll_add_exclusive(epfd: RawFd, fd: RawFd, info: &EpollEvent) -> io::Result<()> {
+    use libc;
+
+    let mut event = libc::epoll_event {
+        events: info.events.bits() | EPOLLEXCLUSIVE,
+        u64: info.data,
+    };
+
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event) };
+
+    if res < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
     }
 }
 
@@ -98,7 +251,9 @@ fn ioevent_to_epoll(interest: EventSet, opts: PollOpt) -> EpollEventKind {
     let mut kind = EpollEventKind::empty();
 
     if interest.is_readable() {
-        kind.insert(EPOLLIN);
+        // kqueue always reports EOF on the read filter, also ask for it here
+        // so that `hup` is reported consistently across platforms
+        kind.insert(EPOLLIN | EPOLLRDHUP);
     }
 
     if interest.is_writable() {
@@ -109,11 +264,19 @@ fn ioevent_to_epoll(interest: EventSet, opts: PollOpt) -> EpollEventKind {
         kind.insert(EPOLLRDHUP);
     }
 
+    // EPOLLEXCLUSIVE rejects EPOLLRDHUP, such registrations still get EPOLLHUP
+    if opts.is_exclusive() {
+        kind.remove(EPOLLRDHUP);
+    }
+
     if opts.is_edge() {
         kind.insert(EPOLLET);
     }
 
-    if opts.is_oneshot() {
+    // Auto deregistration happens in `select`, also disable the fd in the
+    // kernel so that clones of the selector don't receive further events.
+    // EPOLLEXCLUSIVE can't be combined with EPOLLONESHOT.
+    if opts.is_oneshot() || (opts.is_auto_deregister() && !opts.is_exclusive()) {
         kind.insert(EPOLLONESHOT);
     }
 
@@ -136,11 +299,22 @@ pub struct Events {
 
 impl Events {
     pub fn new() -> Events {
+        Events::with_capacity(1024)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Events {
         Events {
-            events: Vec::with_capacity(1024),
+            events: Vec::with_capacity(capacity),
         }
     }
 
+    /// Whether the last `select` filled the whole buffer, in which case more
+    /// events may be pending.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.events.len() == self.events.capacity()
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.events.len()
@@ -151,6 +325,15 @@ impl Events {
         self.events.is_empty()
     }
 
+    pub fn remove_token(&mut self, token: Token) {
+        let data = token.as_usize() as u64;
+        self.events.retain(|event| event.data != data);
+    }
+
+    pub fn sort_by_token(&mut self) {
+        self.events.sort_by(|a, b| a.data.cmp(&b.data));
+    }
+
     #[inline]
     pub fn get(&self, idx: usize) -> Option<Event> {
         self.events.get(idx).map(|event| {
@@ -180,3 +363,45 @@ impl Events {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{epoll_pwait2, Selector};
+    use nix::sys::epoll::{EpollEvent, EpollEventKind};
+    use std::time::Duration;
+    use time::precise_time_ns;
+
+    #[test]
+    pub fn test_epoll_pwait2_sub_millisecond() {
+        let selector = Selector::new().unwrap();
+        let mut dst: Vec<EpollEvent> = (0..8).map(|_| {
+            EpollEvent { events: EpollEventKind::empty(), data: 0 }
+        }).collect();
+
+        let mut min_us = u64::max_value();
+
+        // Keep the fastest run, so that scheduling delays don't fail the test
+        for _ in 0..10 {
+            let start = precise_time_ns();
+
+            match epoll_pwait2(selector.epfd, &mut dst, Duration::new(0, 200_000)) {
+                Some(res) => assert_eq!(0, res.unwrap()),
+                // Not available here, `select` falls back to milliseconds
+                None => {
+                    println!("skipping test_epoll_pwait2_sub_millisecond: epoll_pwait2 is not available");
+                    return;
+                }
+            }
+
+            let elapsed_us = (precise_time_ns() - start) / 1_000;
+
+            assert!(elapsed_us >= 200, "elapsed={}us", elapsed_us);
+
+            if elapsed_us < min_us {
+                min_us = elapsed_us;
+            }
+        }
+
+        assert!(min_us < 1_000, "timeout was rounded up; elapsed={}us", min_us);
+    }
+}
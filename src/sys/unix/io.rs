@@ -1,5 +1,6 @@
 use {io, poll, Evented, EventSet, Poll, PollOpt, Token};
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::os::unix::io::{IntoRawFd, AsRawFd, FromRawFd, RawFd};
 use nix::fcntl::FcntlArg::F_SETFL;
 use nix::fcntl::{fcntl, O_NONBLOCK};
@@ -19,17 +20,33 @@ pub fn set_nonblock(s: &AsRawFd) -> io::Result<()> {
 #[derive(Debug)]
 pub struct Io {
     fd: RawFd,
+    // ID of the selector the fd was first registered with, zero until then
+    selector_id: AtomicUsize,
 }
 
 impl Io {
     pub fn from_raw_fd(fd: RawFd) -> Io {
-        Io { fd: fd }
+        Io {
+            fd: fd,
+            selector_id: AtomicUsize::new(0),
+        }
+    }
+
+    fn associate_selector(&self, poll: &Poll) -> io::Result<()> {
+        let id = poll::selector(poll).id();
+        let prev = self.selector_id.compare_and_swap(0, id, Ordering::Relaxed);
+
+        if prev != 0 && prev != id {
+            Err(io::Error::new(io::ErrorKind::Other, "fd already registered"))
+        } else {
+            Ok(())
+        }
     }
 }
 
 impl From<RawFd> for Io {
     fn from(fd: RawFd) -> Io {
-        Io { fd: fd }
+        Io::from_raw_fd(fd)
     }
 }
 
@@ -53,6 +70,7 @@ impl AsRawFd for Io {
 
 impl Evented for Io {
     fn register(&self, poll: &mut Poll, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        try!(self.associate_selector(poll));
         poll::selector_mut(poll).register(self.fd, token, interest, opts)
     }
 
@@ -1,13 +1,16 @@
 use {io, EventSet, PollOpt, Token};
 use event::{self, Event};
-use nix::unistd::close;
+use nix::unistd::{close, dup};
 use nix::sys::event::{EventFilter, EventFlag, FilterFlag, KEvent, kqueue, kevent, kevent_ts};
-use nix::sys::event::{EV_ADD, EV_CLEAR, EV_DELETE, EV_DISABLE, EV_ENABLE, EV_EOF, EV_ERROR, EV_ONESHOT};
+use nix::sys::event::{EV_ADD, EV_CLEAR, EV_DELETE, EV_DISABLE, EV_ENABLE, EV_EOF, EV_ERROR, EV_ONESHOT, EV_RECEIPT};
 use libc::{timespec, time_t, c_long};
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+use nix::sys::event::NOTE_TRIGGER;
 use std::{fmt, slice};
 use std::os::unix::io::RawFd;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::time::Duration;
 
 /// Each Selector has a globally unique(ish) ID associated with it. This ID
 /// gets tracked by `TcpStream`, `TcpListener`, etc... when they are first
@@ -20,18 +23,24 @@ static NEXT_ID: AtomicUsize = ATOMIC_USIZE_INIT;
 pub struct Selector {
     id: usize,
     kq: RawFd,
-    changes: Events
+    changes: Events,
+    // Whether register changes are deferred until `end_batch`
+    batching: bool,
+    auto: super::AutoDeregister,
 }
 
 impl Selector {
     pub fn new() -> io::Result<Selector> {
-        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        // IDs start at one, so that zero can stand for "no selector"
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed) + 1;
         let kq = try!(kqueue().map_err(super::from_nix_error));
 
         Ok(Selector {
             id: id,
             kq: kq,
-            changes: Events::new()
+            changes: Events::new(),
+            batching: false,
+            auto: super::AutoDeregister::new(),
         })
     }
 
@@ -39,10 +48,27 @@ impl Selector {
         self.id
     }
 
-    pub fn select(&mut self, evts: &mut Events, timeout_ms: Option<usize>) -> io::Result<()> {
-        let timeout = timeout_ms.map(|x| timespec {
-            tv_sec: (x / 1000) as time_t,
-            tv_nsec: ((x % 1000) * 1_000_000) as c_long
+    /// Duplicates the kqueue fd. The clone keeps the same ID since it refers
+    /// to the same kernel object.
+    pub fn try_clone(&self) -> io::Result<Selector> {
+        let kq = try!(dup(self.kq).map_err(super::from_nix_error));
+
+        Ok(Selector {
+            id: self.id,
+            kq: kq,
+            changes: Events::new(),
+            batching: false,
+            auto: self.auto.clone(),
+        })
+    }
+
+    pub fn select(&mut self, evts: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
+        use std::{cmp, i32};
+
+        // Clamp so that the seconds fit in a 32 bit `time_t`
+        let timeout = timeout.map(|to| timespec {
+            tv_sec: cmp::min(to.as_secs(), i32::MAX as u64) as time_t,
+            tv_nsec: to.subsec_nanos() as c_long
         });
 
         let cnt = try!(kevent_ts(self.kq, &[], evts.as_mut_slice(), timeout)
@@ -56,6 +82,18 @@ impl Selector {
 
         evts.coalesce();
 
+        for i in 0..evts.len() {
+            let token = evts.events[i].token();
+
+            if let Some(fd) = self.auto.take(token) {
+                // The events have been read already, so a failure must not
+                // lose them. The fd is disabled by EV_ONESHOT regardless.
+                if let Err(err) = self.deregister(fd) {
+                    warn!("failed to auto deregister; fd={}; err={:?}", fd, err);
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -65,6 +103,23 @@ impl Selector {
         self.ev_register(fd, token.as_usize(), EventFilter::EVFILT_READ, interests.contains(EventSet::readable()), opts);
         self.ev_register(fd, token.as_usize(), EventFilter::EVFILT_WRITE, interests.contains(EventSet::writable()), opts);
 
+        self.auto.update(fd, token, opts);
+
+        if self.batching {
+            return Ok(());
+        }
+
+        self.flush_changes()
+    }
+
+    /// Defers the changes made by `register` and `reregister` until
+    /// `end_batch`, which submits them all with a single `kevent` call.
+    pub fn begin_batch(&mut self) {
+        self.batching = true;
+    }
+
+    pub fn end_batch(&mut self) -> io::Result<()> {
+        self.batching = false;
         self.flush_changes()
     }
 
@@ -75,10 +130,51 @@ impl Selector {
     }
 
     pub fn deregister(&mut self, fd: RawFd) -> io::Result<()> {
-        self.ev_push(fd, 0, EventFilter::EVFILT_READ, EV_DELETE);
-        self.ev_push(fd, 0, EventFilter::EVFILT_WRITE, EV_DELETE);
+        // Changes deferred by a batch don't ask for a receipt
+        if !self.changes.sys_events.is_empty() {
+            try!(self.flush_changes());
+        }
 
-        self.flush_changes()
+        self.remove(fd)
+    }
+
+    /// Deregisters `fd` like `deregister`, bypassing the change list so that
+    /// this only needs a shared reference.
+    pub fn remove(&self, fd: RawFd) -> io::Result<()> {
+        self.auto.remove_fd(fd);
+
+        // With EV_RECEIPT, the outcome of each change is returned in the
+        // eventlist, so a missing filter doesn't keep the other one from
+        // being deleted. As there is one slot per change, no pending events
+        // are drained.
+        let changes = [
+            change(fd, 0, EventFilter::EVFILT_READ, EV_DELETE | EV_RECEIPT),
+            change(fd, 0, EventFilter::EVFILT_WRITE, EV_DELETE | EV_RECEIPT),
+        ];
+
+        let mut receipts: Vec<KEvent> = Vec::with_capacity(changes.len());
+
+        let cnt = try!({
+            let dst = unsafe {
+                slice::from_raw_parts_mut(receipts.as_mut_ptr(), receipts.capacity())
+            };
+
+            kevent(self.kq, &changes, dst, 0)
+                .map_err(super::from_nix_error)
+        });
+
+        unsafe {
+            receipts.set_len(cnt);
+        }
+
+        for e in &receipts {
+            if e.flags.contains(EV_ERROR) && e.data != 0 {
+                let err = io::Error::from_raw_os_error(e.data as i32);
+                try!(super::ignore_deregistered(Err(err)));
+            }
+        }
+
+        Ok(())
     }
 
     fn ev_register(&mut self, fd: RawFd, token: usize, filter: EventFilter, enable: bool, opts: PollOpt) {
@@ -94,37 +190,33 @@ impl Selector {
             flags = flags | EV_CLEAR;
         }
 
-        if opts.contains(PollOpt::oneshot()) {
+        // Auto deregistration happens in `select`, until then the kernel
+        // should report the handle only once
+        if opts.contains(PollOpt::oneshot()) || opts.is_auto_deregister() {
             flags = flags | EV_ONESHOT;
         }
 
         self.ev_push(fd, token, filter, flags);
     }
 
-    #[cfg(not(target_os = "netbsd"))]
-    fn ev_push(&mut self, fd: RawFd, token: usize, filter: EventFilter, flags: EventFlag) {
-        self.changes.sys_events.push(
-            KEvent {
-                ident: fd as ::libc::uintptr_t,
-                filter: filter,
-                flags: flags,
-                fflags: FilterFlag::empty(),
-                data: 0,
-                udata: token
-            });
-    }
-
-    #[cfg(target_os = "netbsd")]
+    /// Adds a user event identified by `ident`, which is reported under
+    /// `token` as readable each time it is triggered with `trigger_user`.
+    #[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+    pub fn register_user(&mut self, ident: usize, token: Token) -> io::Result<()> {
+        // EV_CLEAR resets the event once it has been returned by `select`
+        self.changes.sys_events.push(user_event(ident, token.as_usize(), EV_ADD | EV_CLEAR, FilterFlag::empty()));
+        self.flush_changes()
+    }
+
+    /// Duplicates the kqueue fd, so that user events can be triggered from
+    /// other threads.
+    #[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+    pub fn dup_fd(&self) -> io::Result<RawFd> {
+        dup(self.kq).map_err(super::from_nix_error)
+    }
+
     fn ev_push(&mut self, fd: RawFd, token: usize, filter: EventFilter, flags: EventFlag) {
-        self.changes.sys_events.push(
-            KEvent {
-                ident: fd as ::libc::uintptr_t,
-                filter: filter,
-                flags: flags,
-                fflags: FilterFlag::empty(),
-                data: 0,
-                udata: token as i64
-            });
+        self.changes.sys_events.push(change(fd, token, filter, flags));
     }
 
     fn flush_changes(&mut self) -> io::Result<()> {
@@ -136,6 +228,51 @@ impl Selector {
     }
 }
 
+#[cfg(not(target_os = "netbsd"))]
+fn change(fd: RawFd, token: usize, filter: EventFilter, flags: EventFlag) -> KEvent {
+    KEvent {
+        ident: fd as ::libc::uintptr_t,
+        filter: filter,
+        flags: flags,
+        fflags: FilterFlag::empty(),
+        data: 0,
+        udata: token
+    }
+}
+
+#[cfg(target_os = "netbsd")]
+fn change(fd: RawFd, token: usize, filter: EventFilter, flags: EventFlag) -> KEvent {
+    KEvent {
+        ident: fd as ::libc::uintptr_t,
+        filter: filter,
+        flags: flags,
+        fflags: FilterFlag::empty(),
+        data: 0,
+        udata: token as i64
+    }
+}
+
+/// Triggers the user event `ident` previously added to the kqueue `kq` with
+/// `Selector::register_user`.
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+pub fn trigger_user(kq: RawFd, ident: usize) -> io::Result<()> {
+    kevent(kq, &[user_event(ident, 0, EventFlag::empty(), NOTE_TRIGGER)], &mut [], 0)
+        .map(|_| ())
+        .map_err(super::from_nix_error)
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+fn user_event(ident: usize, token: usize, flags: EventFlag, fflags: FilterFlag) -> KEvent {
+    KEvent {
+        ident: ident as ::libc::uintptr_t,
+        filter: EventFilter::EVFILT_USER,
+        flags: flags,
+        fflags: fflags,
+        data: 0,
+        udata: token
+    }
+}
+
 impl Drop for Selector {
     fn drop(&mut self) {
         let _ = close(self.kq);
@@ -150,13 +287,24 @@ pub struct Events {
 
 impl Events {
     pub fn new() -> Events {
+        Events::with_capacity(1024)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Events {
         Events {
-            sys_events: Vec::with_capacity(1024),
-            events: Vec::with_capacity(1024),
-            event_map: HashMap::with_capacity(1024)
+            sys_events: Vec::with_capacity(capacity),
+            events: Vec::with_capacity(capacity),
+            event_map: HashMap::with_capacity(capacity)
         }
     }
 
+    /// Whether the last `select` filled the whole buffer, in which case more
+    /// events may be pending.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.sys_events.len() == self.sys_events.capacity()
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.events.len()
@@ -171,6 +319,14 @@ impl Events {
         self.events.get(idx).map(|e| *e)
     }
 
+    pub fn remove_token(&mut self, token: Token) {
+        self.events.retain(|e| e.token() != token);
+    }
+
+    pub fn sort_by_token(&mut self) {
+        self.events.sort_by(|a, b| a.token().cmp(&b.token()));
+    }
+
     pub fn coalesce(&mut self) {
         self.events.clear();
         self.event_map.clear();
@@ -196,6 +352,8 @@ impl Events {
                 event::kind_mut(&mut self.events[idx]).insert(EventSet::readable());
             } else if e.filter == EventFilter::EVFILT_WRITE {
                 event::kind_mut(&mut self.events[idx]).insert(EventSet::writable());
+            } else if is_user(e) {
+                event::kind_mut(&mut self.events[idx]).insert(EventSet::readable());
             }
 
             if e.flags.contains(EV_EOF) {
@@ -225,6 +383,16 @@ impl Events {
     }
 }
 
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+fn is_user(e: &KEvent) -> bool {
+    e.filter == EventFilter::EVFILT_USER
+}
+
+#[cfg(not(any(target_os = "freebsd", target_os = "ios", target_os = "macos")))]
+fn is_user(_: &KEvent) -> bool {
+    false
+}
+
 impl fmt::Debug for Events {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         write!(fmt, "Events {{ len: {} }}", self.sys_events.len())
@@ -31,6 +31,12 @@ pub use self::tcp::{TcpStream, TcpListener};
 pub use self::udp::UdpSocket;
 pub use self::uds::UnixSocket;
 
+use {PollOpt, Token};
+use std::collections::HashMap;
+use std::os::unix::io::RawFd;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 pub fn pipe() -> ::io::Result<(Io, Io)> {
     use nix::fcntl::{O_NONBLOCK, O_CLOEXEC};
     use nix::unistd::pipe2;
@@ -41,10 +47,183 @@ pub fn pipe() -> ::io::Result<(Io, Io)> {
     Ok((Io::from_raw_fd(rd), Io::from_raw_fd(wr)))
 }
 
+/// Tracks the fds registered with `PollOpt::auto_deregister`, so that
+/// selectors can remove them once they fire.
+///
+/// Clones share the same state, so that a selector and the clones created by
+/// `try_clone` agree on which fds are left to deregister.
+#[derive(Clone, Debug)]
+pub struct AutoDeregister {
+    inner: Arc<AutoDeregisterInner>,
+}
+
+#[derive(Debug)]
+struct AutoDeregisterInner {
+    // Number of tracked fds, so that the common case of there being none
+    // doesn't take the lock
+    len: AtomicUsize,
+    fds: Mutex<AutoFds>,
+}
+
+#[derive(Debug)]
+struct AutoFds {
+    by_token: HashMap<Token, RawFd>,
+    by_fd: HashMap<RawFd, Token>,
+}
+
+impl AutoDeregister {
+    pub fn new() -> AutoDeregister {
+        AutoDeregister {
+            inner: Arc::new(AutoDeregisterInner {
+                len: AtomicUsize::new(0),
+                fds: Mutex::new(AutoFds {
+                    by_token: HashMap::new(),
+                    by_fd: HashMap::new(),
+                }),
+            }),
+        }
+    }
+
+    /// Records a registration or reregistration of `fd`. Returns the entries
+    /// it displaced, to hand to `restore` if the registration fails.
+    pub fn update(&self, fd: RawFd, token: Token, opts: PollOpt) -> Vec<(RawFd, Token)> {
+        if !opts.is_auto_deregister() {
+            return self.remove_fd(fd).into_iter().collect();
+        }
+
+        let mut fds = self.inner.fds.lock().unwrap();
+        let mut displaced = vec![];
+
+        if let Some(prev) = fds.by_token.insert(token, fd) {
+            if prev != fd {
+                fds.by_fd.remove(&prev);
+            }
+
+            displaced.push((prev, token));
+        }
+
+        if let Some(prev) = fds.by_fd.insert(fd, token) {
+            if prev != token {
+                fds.by_token.remove(&prev);
+                displaced.push((fd, prev));
+            }
+        }
+
+        self.inner.len.store(fds.by_token.len(), Ordering::Release);
+        displaced
+    }
+
+    /// Undoes an `update` of `fd` with `token`, putting back the entries it
+    /// displaced.
+    pub fn restore(&self, fd: RawFd, token: Token, displaced: Vec<(RawFd, Token)>) {
+        let mut fds = self.inner.fds.lock().unwrap();
+
+        if fds.by_fd.get(&fd) == Some(&token) {
+            fds.by_fd.remove(&fd);
+            fds.by_token.remove(&token);
+        }
+
+        for (fd, token) in displaced {
+            fds.by_fd.insert(fd, token);
+            fds.by_token.insert(token, fd);
+        }
+
+        self.inner.len.store(fds.by_token.len(), Ordering::Release);
+    }
+
+    /// Stops tracking `fd`, returning the token it was tracked under.
+    pub fn remove_fd(&self, fd: RawFd) -> Option<(RawFd, Token)> {
+        if self.inner.len.load(Ordering::Acquire) == 0 {
+            return None;
+        }
+
+        let mut fds = self.inner.fds.lock().unwrap();
+        let token = fds.by_fd.remove(&fd);
+
+        if let Some(token) = token {
+            fds.by_token.remove(&token);
+        }
+
+        self.inner.len.store(fds.by_token.len(), Ordering::Release);
+        token.map(|token| (fd, token))
+    }
+
+    /// Returns the fd to deregister if `token` was registered with
+    /// `PollOpt::auto_deregister`. Only one caller gets the fd.
+    pub fn take(&self, token: Token) -> Option<RawFd> {
+        if self.inner.len.load(Ordering::Acquire) == 0 {
+            return None;
+        }
+
+        let mut fds = self.inner.fds.lock().unwrap();
+        let fd = fds.by_token.remove(&token);
+
+        if let Some(fd) = fd {
+            fds.by_fd.remove(&fd);
+        }
+
+        self.inner.len.store(fds.by_token.len(), Ordering::Release);
+        fd
+    }
+}
+
+/// Whether `err` reports that the process or the system ran out of file
+/// descriptors.
+pub fn is_fd_exhaustion(err: &::io::Error) -> bool {
+    match err.raw_os_error() {
+        Some(::libc::EMFILE) | Some(::libc::ENFILE) => true,
+        _ => false,
+    }
+}
+
+/// Pins the calling thread to the given CPU.
+#[cfg(target_os = "linux")]
+pub fn set_cpu_affinity(cpu: usize) -> ::io::Result<()> {
+    use libc::{c_int, c_ulong, pid_t, size_t};
+    use std::mem;
+
+    // Same size as glibc's `cpu_set_t`
+    const CPU_SETSIZE: usize = 1024;
+
+    extern {
+        fn sched_setaffinity(pid: pid_t, cpusetsize: size_t, mask: *const c_ulong) -> c_int;
+    }
+
+    if cpu >= CPU_SETSIZE {
+        return Err(::io::Error::new(::io::ErrorKind::InvalidInput, "cpu index out of range"));
+    }
+
+    let bits = 8 * mem::size_of::<c_ulong>();
+    let mut set = vec![0 as c_ulong; CPU_SETSIZE / bits];
+    set[cpu / bits] |= 1 << (cpu % bits);
+
+    // A pid of 0 refers to the calling thread
+    let res = unsafe {
+        sched_setaffinity(0, (set.len() * mem::size_of::<c_ulong>()) as size_t, set.as_ptr())
+    };
+
+    if res < 0 {
+        Err(::io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
 pub fn from_nix_error(err: ::nix::Error) -> ::io::Error {
     ::io::Error::from_raw_os_error(err.errno() as i32)
 }
 
+/// Maps the errors returned when deregistering an fd that was already closed
+/// (`EBADF`) or is not registered (`ENOENT`) to success. Closing an fd removes
+/// it from the selector, so there is nothing left to clean up.
+fn ignore_deregistered(res: ::io::Result<()>) -> ::io::Result<()> {
+    match res {
+        Err(ref e) if e.raw_os_error() == Some(::libc::EBADF) ||
+                      e.raw_os_error() == Some(::libc::ENOENT) => Ok(()),
+        res => res,
+    }
+}
+
 mod nix {
     pub use nix::{
         c_int,
@@ -5,10 +5,7 @@ use std::net::{self, SocketAddr};
 use std::os::unix::io::{RawFd, FromRawFd, IntoRawFd, AsRawFd};
 
 use libc;
-use net2::TcpStreamExt;
-
-#[allow(unused_imports)]
-use net2::TcpListenerExt;
+use net2::{TcpStreamExt, TcpListenerExt};
 
 use {io, poll, Evented, EventSet, Poll, PollOpt, Token};
 
@@ -64,10 +61,46 @@ impl TcpStream {
         self.inner.shutdown(how)
     }
 
+    pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        let ret = unsafe {
+            libc::recv(self.as_raw_fd(),
+                       buf.as_mut_ptr() as *mut libc::c_void,
+                       buf.len() as libc::size_t,
+                       libc::MSG_PEEK)
+        };
+
+        if ret < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(ret as usize)
+        }
+    }
+
+    pub fn poll_write_ready(&self) -> io::Result<bool> {
+        let mut fd = libc::pollfd {
+            fd: self.as_raw_fd(),
+            events: libc::POLLOUT,
+            revents: 0,
+        };
+
+        // A zero timeout only checks the current state of the socket
+        let ret = unsafe { libc::poll(&mut fd, 1, 0) };
+
+        if ret < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(fd.revents & libc::POLLOUT != 0)
+        }
+    }
+
     pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
         TcpStreamExt::set_nodelay(&self.inner, nodelay)
     }
 
+    pub fn nodelay(&self) -> io::Result<bool> {
+        TcpStreamExt::nodelay(&self.inner)
+    }
+
     pub fn set_keepalive(&self, seconds: Option<u32>) -> io::Result<()> {
         self.inner.set_keepalive_ms(seconds.map(|s| s * 1000))
     }
@@ -169,6 +202,22 @@ impl TcpListener {
         })
     }
 
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        TcpListenerExt::set_ttl(&self.inner, ttl)
+    }
+
+    pub fn ttl(&self) -> io::Result<u32> {
+        TcpListenerExt::ttl(&self.inner)
+    }
+
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        TcpListenerExt::set_only_v6(&self.inner, only_v6)
+    }
+
+    pub fn only_v6(&self) -> io::Result<bool> {
+        TcpListenerExt::only_v6(&self.inner)
+    }
+
     pub fn accept(&self) -> io::Result<Option<(TcpStream, SocketAddr)>> {
         self.inner.accept().and_then(|(s, a)| {
             try!(set_nonblock(&s));
@@ -165,4 +165,10 @@ fn wouldblock() -> io::Error {
     io::Error::new(io::ErrorKind::WouldBlock, "operation would block")
 }
 
+/// Whether `err` reports that no more sockets can be created.
+pub fn is_fd_exhaustion(err: &io::Error) -> bool {
+    // WSAEMFILE
+    err.raw_os_error() == Some(10024)
+}
+
 fn ipv4_any() -> Ipv4Addr { Ipv4Addr::new(0, 0, 0, 0) }
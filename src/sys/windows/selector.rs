@@ -1,14 +1,15 @@
-use std::{fmt, io, mem};
+use std::{cmp, fmt, io, mem, u32};
 use std::cell::UnsafeCell;
 use std::os::windows::prelude::*;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use slab::Index;
 use winapi::*;
 use miow;
 use miow::iocp::{CompletionPort, CompletionStatus};
 
-use {Token, PollOpt};
+use {convert, Token, PollOpt};
 use event::{Event, EventSet};
 use sys::windows::from_raw_arc::FromRawArc;
 use sys::windows::buffer_pool::BufferPool;
@@ -55,16 +56,34 @@ impl Selector {
         })
     }
 
+    /// Returns a new handle to the same completion port.
+    pub fn try_clone(&self) -> io::Result<Selector> {
+        Ok(Selector { inner: self.inner.clone() })
+    }
+
+    /// Registrations are not submitted to the OS one by one, so batching is
+    /// a no-op
+    pub fn begin_batch(&mut self) {
+    }
+
+    pub fn end_batch(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
     pub fn select(&mut self,
                   events: &mut Events,
-                  timeout_ms: Option<usize>) -> io::Result<()> {
+                  timeout: Option<Duration>) -> io::Result<()> {
 
         // If we have some deferred events then we only want to poll for I/O
         // events, so clamp the timeout to 0 in that case.
         let timeout = if !self.should_block() {
             Some(0)
         } else {
-            timeout_ms.map(|ms| ms as u32)
+            // IOCP only has millisecond granularity, round up so that we
+            // don't return early. `INFINITE` is `u32::MAX` so clamp below it.
+            timeout.map(|to| {
+                cmp::min(convert::millis_ceil(to), (u32::MAX - 1) as u64) as u32
+            })
         };
 
         trace!("select; timeout={:?}", timeout);
@@ -80,6 +99,8 @@ impl Selector {
             Err(e) => return Err(e),
         };
 
+        events.full = n == events.statuses.len();
+
         // First up, process all completed I/O events. Lookup the callback
         // associated with the I/O and invoke it. Also, carefully don't hold any
         // locks while we invoke a callback in case more I/O is scheduled to
@@ -301,7 +322,7 @@ impl Registration {
                 trace!("pushing event; event={:?}", self.event);
             }
 
-            if self.opts.is_oneshot() {
+            if self.opts.is_oneshot() || self.opts.is_auto_deregister() {
                 trace!("deregistering because of oneshot");
                 self.interest = EventSet::none();
             }
@@ -441,6 +462,9 @@ pub struct Events {
 
     /// Literal events returned by `get` to the upwards `EventLoop`
     events: Vec<Event>,
+
+    /// Whether the last call to `get_many` filled all of `statuses`
+    full: bool,
 }
 
 impl Events {
@@ -452,12 +476,21 @@ impl Events {
         // Note that it's possible for the output `events` to grow beyond 1024
         // capacity as it can also include deferred events, but that's certainly
         // not the end of the world!
+        Events::with_capacity(1024)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Events {
         Events {
-            statuses: vec![CompletionStatus::zero(); 1024].into_boxed_slice(),
-            events: Vec::with_capacity(1024),
+            statuses: vec![CompletionStatus::zero(); capacity].into_boxed_slice(),
+            events: Vec::with_capacity(capacity),
+            full: false,
         }
     }
 
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
     pub fn is_empty(&self) -> bool {
         self.events.is_empty()
     }
@@ -469,6 +502,14 @@ impl Events {
     pub fn get(&self, idx: usize) -> Option<Event> {
         self.events.get(idx).map(|e| *e)
     }
+
+    pub fn remove_token(&mut self, token: Token) {
+        self.events.retain(|e| e.token() != token);
+    }
+
+    pub fn sort_by_token(&mut self) {
+        self.events.sort_by(|a, b| a.token().cmp(&b.token()));
+    }
 }
 
 macro_rules! overlapped2arc {
@@ -123,10 +123,40 @@ impl TcpStream {
         self.inner().socket.shutdown(how)
     }
 
+    /// Data is read into an intermediate buffer by the overlapped read, so
+    /// peeking copies out of that buffer without advancing it.
+    pub fn peek(&self, mut buf: &mut [u8]) -> io::Result<usize> {
+        let me = self.inner();
+
+        match me.read {
+            State::Ready(ref cursor) => {
+                let pos = cursor.position() as usize;
+                buf.write(&cursor.get_ref()[pos..])
+            }
+            State::Error(ref e) => Err(io::Error::new(e.kind(), e.to_string())),
+            _ => Err(wouldblock()),
+        }
+    }
+
+    pub fn poll_write_ready(&self) -> io::Result<bool> {
+        let me = self.inner();
+
+        // Writes are buffered, a new one can start once the last completed
+        match me.write {
+            State::Empty => Ok(me.iocp.port().is_some()),
+            State::Error(ref e) => Err(io::Error::new(e.kind(), e.to_string())),
+            _ => Ok(false),
+        }
+    }
+
     pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
         net2::TcpStreamExt::set_nodelay(&self.inner().socket, nodelay)
     }
 
+    pub fn nodelay(&self) -> io::Result<bool> {
+        net2::TcpStreamExt::nodelay(&self.inner().socket)
+    }
+
     pub fn set_keepalive(&self, seconds: Option<u32>) -> io::Result<()> {
         let dur = seconds.map(|s| s * 1000);
         net2::TcpStreamExt::set_keepalive_ms(&self.inner().socket, dur)
@@ -499,6 +529,22 @@ impl TcpListener {
         })
     }
 
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        net2::TcpListenerExt::set_ttl(&self.inner().socket, ttl)
+    }
+
+    pub fn ttl(&self) -> io::Result<u32> {
+        net2::TcpListenerExt::ttl(&self.inner().socket)
+    }
+
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        net2::TcpListenerExt::set_only_v6(&self.inner().socket, only_v6)
+    }
+
+    pub fn only_v6(&self) -> io::Result<bool> {
+        net2::TcpListenerExt::only_v6(&self.inner().socket)
+    }
+
     pub fn take_socket_error(&self) -> io::Result<()> {
         net2::TcpListenerExt::take_error(&self.inner().socket).and_then(|e| {
             match e {
@@ -0,0 +1,137 @@
+use {NotifyError, Sender};
+use time::precise_time_ns;
+use std::{any, cmp, error, fmt};
+use std::time::Duration;
+
+const NS_PER_SEC: u64 = 1_000_000_000;
+
+/// Rate limits messages sent to an `EventLoop` using a token bucket.
+///
+/// The bucket holds up to `burst` tokens and is refilled with one token every
+/// `interval`. Each message sent takes a token, and `try_send` fails with
+/// `ThrottleError::RateLimited` when the bucket is empty.
+///
+/// The refill is not scheduled with the event loop's `Timer`, which can only
+/// be driven from the event loop thread while senders live on other threads.
+/// Instead, the tokens earned since the last send are computed from the
+/// monotonic clock on each call to `try_send`, so the interval is not limited
+/// to the timer's tick either.
+pub struct ThrottledSender<M> {
+    sender: Sender<M>,
+    burst: u64,
+    tokens: u64,
+    // Refill interval in nanoseconds
+    interval: u64,
+    // Time of the last refill in nanoseconds
+    last: u64,
+}
+
+impl<M> ThrottledSender<M> {
+    /// Creates a new `ThrottledSender` with a full bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `burst` or `interval` is zero.
+    pub fn new(sender: Sender<M>, burst: usize, interval: Duration) -> ThrottledSender<M> {
+        let interval = interval.as_secs().saturating_mul(NS_PER_SEC)
+            .saturating_add(interval.subsec_nanos() as u64);
+
+        assert!(burst > 0, "burst must not be zero");
+        assert!(interval > 0, "interval must not be zero");
+
+        ThrottledSender {
+            sender: sender,
+            burst: burst as u64,
+            tokens: burst as u64,
+            interval: interval,
+            last: precise_time_ns(),
+        }
+    }
+
+    pub fn try_send(&mut self, msg: M) -> Result<(), ThrottleError<M>> {
+        self.refill();
+
+        if self.tokens == 0 {
+            return Err(ThrottleError::RateLimited(msg));
+        }
+
+        try!(self.sender.send(msg));
+        self.tokens -= 1;
+
+        Ok(())
+    }
+
+    /// Returns the underlying `Sender`, which is not rate limited.
+    pub fn sender(&self) -> &Sender<M> {
+        &self.sender
+    }
+
+    fn refill(&mut self) {
+        let now = precise_time_ns();
+        let n = (now - self.last) / self.interval;
+
+        if n == 0 {
+            return;
+        }
+
+        self.tokens = cmp::min(self.burst, self.tokens.saturating_add(n));
+        self.last += n * self.interval;
+    }
+}
+
+impl<M> fmt::Debug for ThrottledSender<M> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "ThrottledSender<?> {{ tokens: {}, burst: {} }}", self.tokens, self.burst)
+    }
+}
+
+pub enum ThrottleError<T> {
+    /// The bucket is empty, the message was not sent.
+    RateLimited(T),
+    /// Sending the message to the event loop failed.
+    Notify(NotifyError<T>),
+}
+
+impl<M> From<NotifyError<M>> for ThrottleError<M> {
+    fn from(src: NotifyError<M>) -> ThrottleError<M> {
+        ThrottleError::Notify(src)
+    }
+}
+
+impl<M> fmt::Debug for ThrottleError<M> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ThrottleError::RateLimited(..) => {
+                write!(fmt, "ThrottleError::RateLimited(..)")
+            }
+            ThrottleError::Notify(ref e) => {
+                write!(fmt, "ThrottleError::Notify({:?})", e)
+            }
+        }
+    }
+}
+
+impl<M> fmt::Display for ThrottleError<M> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ThrottleError::RateLimited(..) => write!(fmt, "Rate limited"),
+            ThrottleError::Notify(ref e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl<M: any::Any> error::Error for ThrottleError<M> {
+    fn description(&self) -> &str {
+        match *self {
+            ThrottleError::RateLimited(..) => "Rate limit exceeded",
+            ThrottleError::Notify(ref err) => err.description(),
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            ThrottleError::Notify(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
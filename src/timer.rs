@@ -71,8 +71,11 @@ impl<T> Timer<T> {
             return None;
         }
 
+        // `tick_to` leaves `tick` at the first tick it hasn't processed yet.
+        // Nothing is ever scheduled for tick 0, so before the first call the
+        // next tick is 1.
         let now = self.now_ms();
-        let nxt = self.start + (self.tick + 1) * self.tick_ms;
+        let nxt = self.start.saturating_add(max(self.tick, 1).saturating_mul(self.tick_ms));
 
         if nxt <= now {
             return Some(0);
@@ -87,7 +90,7 @@ impl<T> Timer<T> {
      *
      */
 
-    // Sets the starting time of the timer using the current system time
+    // Sets the starting time of the timer using the current monotonic time
     pub fn setup(&mut self) {
         let now = self.now_ms();
         self.set_start_ms(now);
@@ -105,50 +108,99 @@ impl<T> Timer<T> {
      */
 
     pub fn timeout_ms(&mut self, token: T, delay: u64) -> TimerResult<Timeout> {
-        let at = self.now_ms() + max(0, delay);
+        // Saturate, a timeout too far in the future to represent never fires
+        let at = self.now_ms().saturating_add(delay);
         self.timeout_at_ms(token, at)
     }
 
-    pub fn timeout_at_ms(&mut self, token: T, mut at: u64) -> TimerResult<Timeout> {
-        // Make relative to start
-        at -= self.start;
-        // Calculate tick
-        let mut tick = (at + self.tick_ms - 1) / self.tick_ms;
+    pub fn timeout_at_ms(&mut self, token: T, at: u64) -> TimerResult<Timeout> {
+        let tick = self.tick_for_ms(at);
+        self.insert(token, tick, None)
+    }
 
-        // Always target at least 1 tick in the future
-        if tick <= self.tick {
-            tick = self.tick + 1;
-        }
+    // Sets a timeout that first fires after `period` ms and is then re-armed
+    // every `period` ms until it is cleared. Each expiration yields a clone
+    // of `token`.
+    pub fn interval_ms(&mut self, token: T, period: u64) -> TimerResult<Timeout>
+        where T: Clone
+    {
+        let at = self.now_ms().saturating_add(period);
+        self.interval_at_ms(token, at, period)
+    }
 
-        self.insert(token, tick)
+    pub fn interval_at_ms(&mut self, token: T, at: u64, period: u64) -> TimerResult<Timeout>
+        where T: Clone
+    {
+        let tick = self.tick_for_ms(at);
+        let repeat = Repeat {
+            // Always re-arm at least 1 tick in the future
+            ticks: max(1, period.saturating_add(self.tick_ms - 1) / self.tick_ms),
+            clone: T::clone,
+        };
+
+        self.insert(token, tick, Some(repeat))
     }
 
     pub fn clear(&mut self, timeout: &Timeout) -> bool {
         let links = match self.entries.get(timeout.token) {
-            Some(e) => e.links,
+            Some(e) => {
+                // Sanity check
+                if e.origin != timeout.tick {
+                    return false;
+                }
+
+                e.links
+            }
             None => return false
         };
 
-        // Sanity check
-        if links.tick != timeout.tick {
-            return false;
-        }
-
         self.unlink(&links, timeout.token);
         self.entries.remove(timeout.token);
         true
     }
 
-    fn insert(&mut self, token: T, tick: u64) -> TimerResult<Timeout> {
-        // Get the slot for the requested tick
-        let slot = (tick & self.mask) as usize;
-        let curr = self.wheel[slot];
+    fn tick_for_ms(&self, mut at: u64) -> u64 {
+        // Make relative to start
+        at -= self.start;
+        // Calculate tick. Entries only fire once their own tick is reached,
+        // so ticks beyond a full rotation of the wheel need no special care.
+        let tick = at.saturating_add(self.tick_ms - 1) / self.tick_ms;
+
+        // Always target at least 1 tick in the future
+        if tick <= self.tick {
+            self.tick + 1
+        } else {
+            tick
+        }
+    }
 
+    fn insert(&mut self, token: T, tick: u64, repeat: Option<Repeat<T>>) -> TimerResult<Timeout> {
         // Insert the new entry
         let token = try!(
-            self.entries.insert(Entry::new(token, tick, curr))
+            self.entries.insert(Entry::new(token, tick, repeat))
             .map_err(|_| TimerError::overflow()));
 
+        self.link(token, tick);
+
+        // Return the new timeout
+        Ok(Timeout {
+            token: token,
+            tick: tick
+        })
+    }
+
+    // Pushes the entry onto the front of the slot for `tick`
+    fn link(&mut self, token: Token, tick: u64) {
+        // Get the slot for the requested tick
+        let slot = self.slot_for(tick);
+        let curr = self.wheel[slot];
+
+        self.entries[token].links = EntryLinks {
+            tick: tick,
+            prev: EMPTY,
+            next: curr,
+        };
+
         if curr != EMPTY {
             // If there was a previous entry, set its prev pointer to the new
             // entry
@@ -159,12 +211,6 @@ impl<T> Timer<T> {
         self.wheel[slot] = token;
 
         trace!("inserted timout; slot={}; token={:?}", slot, token);
-
-        // Return the new timeout
-        Ok(Timeout {
-            token: token,
-            tick: tick
-        })
     }
 
     fn unlink(&mut self, links: &EntryLinks, token: Token) {
@@ -219,6 +265,17 @@ impl<T> Timer<T> {
                     // Unlink will also advance self.next
                     self.unlink(&links, curr);
 
+                    let repeat = self.entries[curr].repeat;
+
+                    if let Some(repeat) = repeat {
+                        // Re-arm in place so that the `Timeout` stays valid.
+                        // If the timer fell behind, skip the missed periods.
+                        let tick = max(links.tick.saturating_add(repeat.ticks), now + 1);
+                        self.link(curr, tick);
+
+                        return Some((repeat.clone)(&self.entries[curr].token));
+                    }
+
                     // Remove and return the token
                     return self.entries.remove(curr)
                         .map(|e| e.token);
@@ -254,6 +311,10 @@ impl<T> Timer<T> {
         (ms - self.start) / self.tick_ms
     }
 
+    // All deadlines are computed from a monotonic clock, so changes to the
+    // system clock do not cause timeouts to fire early or late. Wall-clock
+    // time must not be used here: `ms_to_tick` assumes time never goes
+    // backwards.
     #[inline]
     fn now_ms(&self) -> u64 {
         precise_time_ns() / NS_PER_MS
@@ -265,21 +326,44 @@ impl<T> Timer<T> {
 struct Entry<T> {
     token: T,
     links: EntryLinks,
+    // The tick the entry was first scheduled for, used to validate `Timeout`
+    // handles since repeating entries move to later ticks
+    origin: u64,
+    repeat: Option<Repeat<T>>,
 }
 
 impl<T> Entry<T> {
-    fn new(token: T, tick: u64, next: Token) -> Entry<T> {
+    fn new(token: T, tick: u64, repeat: Option<Repeat<T>>) -> Entry<T> {
         Entry {
             token: token,
             links: EntryLinks {
                 tick: tick,
                 prev: EMPTY,
-                next: next,
+                next: EMPTY,
             },
+            origin: tick,
+            repeat: repeat,
         }
     }
 }
 
+// Re-arming information for interval timeouts
+struct Repeat<T> {
+    // Period in ticks
+    ticks: u64,
+    // Produces the value yielded on each expiration
+    clone: fn(&T) -> T,
+}
+
+// Implemented by hand, deriving would require `T: Copy`
+impl<T> Copy for Repeat<T> {}
+
+impl<T> Clone for Repeat<T> {
+    fn clone(&self) -> Repeat<T> {
+        *self
+    }
+}
+
 #[derive(Copy, Clone)]
 struct EntryLinks {
     tick: u64,
@@ -356,6 +440,21 @@ mod test {
         assert_eq!(t.count(), 0);
     }
 
+    #[test]
+    pub fn test_next_tick_after_tick_to() {
+        let mut t: Timer<&'static str> = Timer::new(1_000, 8, 8);
+        t.setup();
+
+        t.timeout_ms("a", 1_000).unwrap();
+        assert!(t.next_tick_in_ms().unwrap() <= 1_000);
+
+        // Processing the current tick doesn't push the wakeup back past the
+        // tick the timeout is due in
+        let now = t.now();
+        assert_eq!(None, t.tick_to(now));
+        assert!(t.next_tick_in_ms().unwrap() <= 1_000);
+    }
+
     #[test]
     pub fn test_clearing_timeout() {
         let mut t = timer();
@@ -492,6 +591,99 @@ mod test {
         assert_eq!(0, t.count());
     }
 
+    #[test]
+    pub fn test_interval() {
+        let mut t = timer();
+        let mut tick;
+
+        let to = t.interval_at_ms("a", 100, 200).unwrap();
+
+        tick = t.ms_to_tick(100);
+        assert_eq!(Some("a"), t.tick_to(tick));
+        assert_eq!(None, t.tick_to(tick));
+        assert_eq!(1, t.count());
+
+        tick = t.ms_to_tick(200);
+        assert_eq!(None, t.tick_to(tick));
+
+        tick = t.ms_to_tick(300);
+        assert_eq!(Some("a"), t.tick_to(tick));
+        assert_eq!(None, t.tick_to(tick));
+
+        // Clearing stops the recurrence
+        assert!(t.clear(&to));
+        assert_eq!(0, t.count());
+
+        tick = t.ms_to_tick(500);
+        assert_eq!(None, t.tick_to(tick));
+    }
+
+    #[test]
+    pub fn test_interval_catching_up() {
+        let mut t = timer();
+
+        t.interval_at_ms("a", 100, 100).unwrap();
+
+        // Missed periods are skipped, the interval fires once
+        let tick = t.ms_to_tick(600);
+        assert_eq!(Some("a"), t.tick_to(tick));
+        assert_eq!(None, t.tick_to(tick));
+        assert_eq!(1, t.count());
+    }
+
+    #[test]
+    pub fn test_timeout_beyond_wheel_rotation() {
+        let mut t = timer();
+
+        // One rotation of the wheel is TICK * SLOTS = 1600ms
+        t.timeout_at_ms("a", 5_000).unwrap();
+
+        // The slot comes around several times before the timeout is due
+        for ms in (1..50).map(|i| i * TICK) {
+            assert_eq!(None, t.tick_to(t.ms_to_tick(ms)));
+        }
+
+        assert_eq!(Some("a"), t.tick_to(t.ms_to_tick(5_000)));
+        assert_eq!(0, t.count());
+    }
+
+    #[test]
+    pub fn test_timeout_saturates() {
+        use std::u64;
+
+        let mut t = timer();
+
+        t.timeout_at_ms("a", u64::MAX).unwrap();
+        t.interval_at_ms("b", u64::MAX, u64::MAX).unwrap();
+
+        assert_eq!(None, t.tick_to(t.ms_to_tick(10_000)));
+        assert_eq!(2, t.count());
+    }
+
+    #[test]
+    pub fn test_now_is_monotonic() {
+        let t = timer();
+        let mut prev = t.now_ms();
+
+        for _ in 0..10_000 {
+            let now = t.now_ms();
+            assert!(now >= prev, "clock went backwards; prev={}; now={}", prev, now);
+            prev = now;
+        }
+    }
+
+    #[test]
+    pub fn test_deadline_relative_to_start() {
+        let mut t = timer();
+        t.set_start_ms(1_000_000);
+
+        // Deadlines are relative to the monotonic start, whatever its value
+        t.timeout_at_ms("a", 1_000_100).unwrap();
+
+        assert_eq!(None, t.tick_to(t.ms_to_tick(1_000_050)));
+        assert_eq!(Some("a"), t.tick_to(t.ms_to_tick(1_000_100)));
+    }
+
     const TICK: u64 = 100;
     const SLOTS: usize = 16;
 
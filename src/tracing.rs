@@ -0,0 +1,63 @@
+use {NotifyError, Sender};
+use std::fmt;
+use std::sync::Arc;
+
+/// Wraps a `Sender`, invoking a hook with every message it sends.
+///
+/// The hook runs once the message has been accepted by the notify queue,
+/// which makes it a suitable place to record metrics or tracing spans. It is
+/// not called for messages refused with `NotifyError::Full` or
+/// `NotifyError::Closed`. As the event loop may already be handling the
+/// message by then, the hook is given a clone of it, so messages that are
+/// cheap to clone such as `Arc`s are the best fit.
+///
+/// The hook runs outside of the queue, so a slow or panicking hook only
+/// affects the thread calling `send`.
+///
+/// Clones share the same hook.
+pub struct TracingSender<M> {
+    sender: Sender<M>,
+    hook: Arc<Fn(&M) + Send + Sync>,
+}
+
+impl<M> TracingSender<M> {
+    pub fn new<F>(sender: Sender<M>, hook: F) -> TracingSender<M>
+        where F: Fn(&M) + Send + Sync + 'static
+    {
+        TracingSender {
+            sender: sender,
+            hook: Arc::new(hook),
+        }
+    }
+
+    /// Returns the underlying `Sender`, which does not invoke the hook.
+    pub fn sender(&self) -> &Sender<M> {
+        &self.sender
+    }
+}
+
+impl<M: Clone> TracingSender<M> {
+    pub fn send(&self, msg: M) -> Result<(), NotifyError<M>> {
+        let traced = msg.clone();
+
+        try!(self.sender.send(msg));
+        (self.hook)(&traced);
+
+        Ok(())
+    }
+}
+
+impl<M> Clone for TracingSender<M> {
+    fn clone(&self) -> TracingSender<M> {
+        TracingSender {
+            sender: self.sender.clone(),
+            hook: self.hook.clone(),
+        }
+    }
+}
+
+impl<M> fmt::Debug for TracingSender<M> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "TracingSender<?> {{ ... }}")
+    }
+}
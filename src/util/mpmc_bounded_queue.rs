@@ -159,6 +159,11 @@ impl<T> Queue<T> {
     pub fn pop(&self) -> Option<T> {
         self.state.pop()
     }
+
+    /// The capacity, after rounding up to a power of two
+    pub fn capacity(&self) -> usize {
+        self.state.mask + 1
+    }
 }
 
 impl<T> Clone for Queue<T> {
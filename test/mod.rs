@@ -4,18 +4,23 @@ extern crate bytes;
 #[macro_use]
 extern crate log;
 extern crate env_logger;
+extern crate libc;
+extern crate net2;
 extern crate slab;
 extern crate tempdir;
+extern crate time;
 
 pub use ports::localhost;
 
 mod test_battery;
 mod test_close_on_drop;
+mod test_dispatcher;
 mod test_double_register;
 mod test_echo_server;
 mod test_multicast;
 mod test_notify;
 mod test_oneshot;
+mod test_poll;
 mod test_register_deregister;
 mod test_register_multiple_event_loops;
 mod test_reregister_without_poll;
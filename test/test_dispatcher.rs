@@ -0,0 +1,44 @@
+use mio::*;
+use mio::udp::UdpSocket;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+#[test]
+pub fn test_dispatcher() {
+    let mut dispatcher = Dispatcher::new().unwrap();
+
+    let tx = UdpSocket::bound(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let rx = Rc::new(UdpSocket::bound(&"127.0.0.1:0".parse().unwrap()).unwrap());
+    let addr = rx.local_addr().unwrap();
+
+    let received = Rc::new(Cell::new(0));
+
+    {
+        let rx = rx.clone();
+        let received = received.clone();
+
+        dispatcher.register(&*rx, Token(0), EventSet::readable(), PollOpt::edge(), move |event| {
+            assert_eq!(Token(0), event.token());
+            assert!(event.kind().is_readable());
+
+            let mut buf = [0; 16];
+
+            while let Some(_) = rx.recv_from(&mut buf).unwrap() {
+                received.set(received.get() + 1);
+            }
+        }).unwrap();
+    }
+
+    for _ in 0..3 {
+        tx.send_to(b"hello", &addr).unwrap();
+    }
+
+    while received.get() < 3 {
+        dispatcher.run_once(Some(Duration::from_millis(1_000))).unwrap();
+    }
+
+    assert_eq!(3, received.get());
+
+    dispatcher.deregister(&*rx, Token(0)).unwrap();
+}
@@ -3,6 +3,14 @@ use mio::*;
 use mio::tcp::*;
 use std::thread;
 
+// For the tests that only need a channel, the loop is never run
+struct DummyHandler;
+
+impl Handler for DummyHandler {
+    type Timeout = ();
+    type Message = usize;
+}
+
 struct TestHandler {
     sender: Sender<String>,
     notify: usize
@@ -189,3 +197,331 @@ pub fn test_notify_drop() {
 
     handle.join().unwrap();
 }
+
+#[test]
+pub fn test_notify_is_closed() {
+    let event_loop: EventLoop<DummyHandler> = EventLoop::new().unwrap();
+    let sender = event_loop.channel();
+
+    assert!(!sender.is_closed());
+
+    drop(event_loop);
+
+    assert!(sender.is_closed());
+    assert!(sender.send(0).is_err());
+}
+
+#[test]
+pub fn test_notify_dropped_count() {
+    let mut builder = EventLoopBuilder::new();
+    builder.notify_capacity(1);
+
+    let event_loop: EventLoop<DummyHandler> = builder.build().unwrap();
+    let sender = event_loop.channel();
+
+    // The loop isn't running, so the queue eventually fills up
+    let mut i = 0;
+
+    while sender.send(i).is_ok() {
+        i += 1;
+    }
+
+    assert_eq!(1, sender.dropped_count());
+
+    match sender.send(i) {
+        Err(NotifyError::Full(v)) => assert_eq!(i, v),
+        _ => panic!("expected the queue to be full"),
+    }
+
+    assert_eq!(2, sender.dropped_count());
+    assert_eq!(2, event_loop.channel().dropped_count());
+}
+
+#[test]
+pub fn test_throttled_sender() {
+    use std::time::Duration;
+
+    let event_loop: EventLoop<DummyHandler> = EventLoop::new().unwrap();
+    let mut sender = ThrottledSender::new(event_loop.channel(), 2, Duration::from_millis(100));
+
+    let mut limited = 0;
+
+    for i in 0..5 {
+        match sender.try_send(i) {
+            Ok(()) => {}
+            Err(ThrottleError::RateLimited(v)) => {
+                assert_eq!(i, v);
+                limited += 1;
+            }
+            Err(e) => panic!("unexpected error; err={:?}", e),
+        }
+    }
+
+    assert_eq!(3, limited);
+
+    // The bucket refills over time
+    sleep_ms(150);
+    assert!(sender.try_send(5).is_ok());
+}
+
+#[test]
+pub fn test_throttled_sender_sub_millisecond() {
+    use std::time::Duration;
+
+    let event_loop: EventLoop<DummyHandler> = EventLoop::new().unwrap();
+    let mut sender = ThrottledSender::new(event_loop.channel(), 1, Duration::new(0, 100_000));
+
+    // A 100us interval refills the bucket several times within a millisecond
+    for i in 0..3 {
+        assert!(sender.try_send(i).is_ok());
+        sleep_ms(1);
+    }
+}
+
+#[test]
+pub fn test_notify_send_all() {
+    struct Collect {
+        msgs: Vec<usize>,
+        // Number of passes through the loop, and the pass of each message
+        ticks: usize,
+        passes: Vec<usize>,
+    }
+
+    impl Handler for Collect {
+        type Timeout = ();
+        type Message = usize;
+
+        fn notify(&mut self, event_loop: &mut EventLoop<Collect>, msg: usize) {
+            self.msgs.push(msg);
+            self.passes.push(self.ticks);
+
+            if self.msgs.len() == 20 {
+                event_loop.shutdown();
+            }
+        }
+
+        fn tick(&mut self, _: &mut EventLoop<Collect>) {
+            self.ticks += 1;
+        }
+    }
+
+    let mut event_loop = EventLoop::new().unwrap();
+    let sender = event_loop.channel();
+
+    let handle = thread::spawn(move || {
+        let mut handler = Collect { msgs: vec![], ticks: 0, passes: vec![] };
+        event_loop.run(&mut handler).unwrap();
+        handler
+    });
+
+    // Nothing else is registered, so the loop is asleep until the awakener
+    // fires
+    sleep_ms(100);
+    assert_eq!(20, sender.send_all(0..20).unwrap());
+
+    let handler = handle.join().unwrap();
+
+    assert_eq!((0..20).collect::<Vec<usize>>(), handler.msgs);
+
+    // A single wakeup delivered the whole batch in one pass, which was the
+    // last one
+    let pass = handler.passes[0];
+    assert!(handler.passes.iter().all(|&p| p == pass), "passes={:?}", handler.passes);
+    assert_eq!(pass + 1, handler.ticks);
+}
+
+#[test]
+pub fn test_notify_send_all_full() {
+    let mut builder = EventLoopBuilder::new();
+    builder.notify_capacity(4);
+
+    let event_loop: EventLoop<DummyHandler> = builder.build().unwrap();
+    let sender = event_loop.channel();
+
+    match sender.send_all(0..10) {
+        Err((4, NotifyError::Full(4))) => {}
+        res => panic!("unexpected result; res={:?}", res),
+    }
+}
+
+#[test]
+pub fn test_tracing_sender() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let mut builder = EventLoopBuilder::new();
+    builder.notify_capacity(2);
+
+    let event_loop: EventLoop<DummyHandler> = builder.build().unwrap();
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let hook_calls = calls.clone();
+
+    let sender = TracingSender::new(event_loop.channel(), move |_: &usize| {
+        hook_calls.fetch_add(1, Ordering::SeqCst);
+    });
+
+    let mut sent = 0;
+
+    while sender.send(sent).is_ok() {
+        sent += 1;
+    }
+
+    // The hook ran for each accepted message, but not for the refused one
+    assert_eq!(2, sent);
+    assert_eq!(2, calls.load(Ordering::SeqCst));
+
+    drop(event_loop);
+
+    assert!(sender.send(0).is_err());
+    assert_eq!(2, calls.load(Ordering::SeqCst));
+}
+
+#[test]
+pub fn test_tracing_sender_hook_panics() {
+    struct Collect(Vec<usize>);
+
+    impl Handler for Collect {
+        type Timeout = ();
+        type Message = usize;
+
+        fn notify(&mut self, event_loop: &mut EventLoop<Collect>, msg: usize) {
+            self.0.push(msg);
+
+            if self.0.len() == 4 {
+                event_loop.shutdown();
+            }
+        }
+    }
+
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let sender = TracingSender::new(event_loop.channel(), |msg: &usize| {
+        if *msg == 0 {
+            panic!("hook failure");
+        }
+    });
+
+    let first = sender.clone();
+    assert!(thread::spawn(move || first.send(0)).join().is_err());
+
+    // The message was queued before the hook ran, and the queue still works
+    for i in 1..4 {
+        sender.send(i).unwrap();
+    }
+
+    let mut handler = Collect(vec![]);
+    event_loop.run(&mut handler).unwrap();
+
+    assert_eq!(vec![0, 1, 2, 3], handler.0);
+}
+
+#[test]
+pub fn test_notify_send_arc() {
+    use std::sync::Arc;
+
+    struct Shared {
+        expect: Arc<Vec<u8>>,
+        received: usize,
+    }
+
+    impl Handler for Shared {
+        type Timeout = ();
+        type Message = Arc<Vec<u8>>;
+
+        fn notify(&mut self, event_loop: &mut EventLoop<Shared>, msg: Arc<Vec<u8>>) {
+            // The payload was not copied
+            assert_eq!(self.expect.as_ptr(), msg.as_ptr());
+            self.received += 1;
+
+            if self.received == 2 {
+                event_loop.shutdown();
+            }
+        }
+    }
+
+    let mut event_loop = EventLoop::new().unwrap();
+    let payload = Arc::new(vec![1, 2, 3]);
+
+    // Sending an `Arc` only touches the reference count
+    event_loop.channel().send(payload.clone()).unwrap();
+    event_loop.channel().send(payload.clone()).unwrap();
+
+    let mut handler = Shared { expect: payload, received: 0 };
+    event_loop.run(&mut handler).unwrap();
+
+    assert_eq!(2, handler.received);
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+#[test]
+pub fn test_notify_user_event_wakeup() {
+    struct Wakeup;
+
+    impl Handler for Wakeup {
+        type Timeout = ();
+        type Message = ();
+
+        fn notify(&mut self, event_loop: &mut EventLoop<Wakeup>, _: ()) {
+            event_loop.shutdown();
+        }
+    }
+
+    // Nothing but the awakener is registered, so the loop only returns from
+    // `poll` once the user event is triggered
+    let mut event_loop = EventLoop::new().unwrap();
+    let sender = event_loop.channel();
+
+    thread::spawn(move || {
+        sleep_ms(100);
+        sender.send(()).unwrap();
+    });
+
+    event_loop.run(&mut Wakeup).unwrap();
+}
+
+#[test]
+pub fn test_notify_send_counted() {
+    let event_loop: EventLoop<DummyHandler> = EventLoop::new().unwrap();
+    let sender = event_loop.channel();
+
+    // The loop isn't running, so every message stays pending
+    for i in 0..10 {
+        assert_eq!(i + 1, sender.send_counted(i).unwrap());
+    }
+}
+
+#[test]
+pub fn test_sender_is_send_and_sync() {
+    use std::cell::Cell;
+
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    assert_send::<Sender<usize>>();
+    assert_sync::<Sender<usize>>();
+
+    // `Cell` is `Send` but not `Sync`, which is enough for a message
+    assert_send::<Sender<Cell<usize>>>();
+    assert_sync::<Sender<Cell<usize>>>();
+}
+
+#[test]
+pub fn test_notify_sender_capacity() {
+    let mut builder = EventLoopBuilder::new();
+    builder.notify_capacity(8);
+
+    let event_loop: EventLoop<DummyHandler> = builder.build().unwrap();
+    let sender = event_loop.channel();
+
+    assert_eq!(Some(8), sender.capacity());
+
+    // Exactly `capacity` messages fit in the queue
+    assert_eq!(8, sender.send_all(0..20).unwrap_err().0);
+
+    let mut builder = EventLoopBuilder::new();
+    builder.notify_capacity(5);
+
+    let event_loop: EventLoop<DummyHandler> = builder.build().unwrap();
+    assert_eq!(Some(8), event_loop.channel().capacity());
+}
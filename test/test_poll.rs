@@ -0,0 +1,705 @@
+use mio::*;
+use mio::udp::UdpSocket;
+use std::time::Duration;
+use time::precise_time_ns;
+
+// A socket bound to an ephemeral port, most tests only need it to be writable
+fn udp_socket() -> UdpSocket {
+    UdpSocket::bound(&"127.0.0.1:0".parse().unwrap()).unwrap()
+}
+
+#[test]
+pub fn test_poll_now() {
+    let mut poll = Poll::new().unwrap();
+
+    let sock = udp_socket();
+    poll.register(&sock, Token(0), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    // The socket is immediately writable
+    let start = precise_time_ns();
+    let n = poll.poll_now().unwrap();
+    let elapsed_ms = (precise_time_ns() - start) / 1_000_000;
+
+    assert_eq!(1, n);
+    assert_eq!(Token(0), poll.events().get(0).unwrap().token());
+    assert!(elapsed_ms < 100, "elapsed={}ms", elapsed_ms);
+
+    // Edge triggered, so there is nothing left and the call must not block
+    let start = precise_time_ns();
+    assert_eq!(0, poll.poll_now().unwrap());
+    let elapsed_ms = (precise_time_ns() - start) / 1_000_000;
+    assert!(elapsed_ms < 100, "elapsed={}ms", elapsed_ms);
+}
+
+#[test]
+pub fn test_remove_token() {
+    let mut poll = Poll::new().unwrap();
+
+    let a = udp_socket();
+    let b = udp_socket();
+
+    poll.register(&a, Token(0), EventSet::writable(), PollOpt::edge()).unwrap();
+    poll.register(&b, Token(1), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    assert_eq!(2, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+
+    // Deregister `b` and purge its event from the current batch
+    poll.deregister(&b).unwrap();
+    poll.remove_token(Token(1));
+
+    let tokens: Vec<Token> = poll.events().map(|e| e.token()).collect();
+    assert_eq!(vec![Token(0)], tokens);
+    assert_eq!(1, poll.events().len());
+}
+
+#[test]
+pub fn test_register_empty_interest() {
+    use std::io::ErrorKind;
+
+    let mut poll = Poll::new().unwrap();
+    let sock = udp_socket();
+
+    let err = poll.register(&sock, Token(0), EventSet::none(), PollOpt::edge()).unwrap_err();
+    assert_eq!(ErrorKind::InvalidInput, err.kind());
+}
+
+#[test]
+pub fn test_reregister_empty_interest() {
+    let mut poll = Poll::new().unwrap();
+    let sock = udp_socket();
+
+    poll.register(&sock, Token(0), EventSet::writable(), PollOpt::edge()).unwrap();
+    poll.reregister(&sock, Token(0), EventSet::none(), PollOpt::edge()).unwrap();
+
+    // The socket has been quiesced
+    assert_eq!(0, poll.poll(Some(Duration::from_millis(100))).unwrap());
+}
+
+#[test]
+pub fn test_poll_sub_millisecond_timeout() {
+    let mut poll = Poll::new().unwrap();
+    let mut min_us = u64::max_value();
+
+    for _ in 0..10 {
+        let start = precise_time_ns();
+        assert_eq!(0, poll.poll(Some(Duration::new(0, 200_000))).unwrap());
+        let elapsed_us = (precise_time_ns() - start) / 1_000;
+
+        // The timeout must never be truncated to zero
+        assert!(elapsed_us >= 200, "elapsed={}us", elapsed_us);
+
+        if elapsed_us < min_us {
+            min_us = elapsed_us;
+        }
+    }
+
+    // Without a precise timeout, the wait is rounded up to a millisecond, or
+    // to the timer resolution on Windows
+    let max_us = if precise_timeouts() { 1_000 } else { 20_000 };
+    assert!(min_us < max_us, "elapsed={}us; precise={}", min_us, precise_timeouts());
+}
+
+// Whether `poll` waits for less than a millisecond. On Linux this needs
+// `epoll_pwait2`, probed the same way as the selector does, with an invalid
+// fd so that an available syscall fails with `EBADF`.
+#[cfg(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64",
+    target_arch = "arm", target_arch = "aarch64", target_arch = "mips",
+    target_arch = "mips64", target_arch = "powerpc", target_arch = "powerpc64",
+    target_arch = "s390x", target_arch = "riscv64")))]
+fn precise_timeouts() -> bool {
+    use libc::{self, c_int, c_void, size_t};
+    use std::{io, ptr};
+
+    let res = unsafe {
+        libc::syscall(libc::SYS_epoll_pwait2,
+                      -1 as c_int,
+                      ptr::null_mut::<c_void>(),
+                      1 as c_int,
+                      ptr::null::<c_void>(),
+                      ptr::null::<c_void>(),
+                      0 as size_t)
+    };
+
+    match io::Error::last_os_error().raw_os_error() {
+        Some(libc::ENOSYS) | Some(libc::EPERM) if res < 0 => false,
+        _ => true,
+    }
+}
+
+#[cfg(any(target_os = "bitrig", target_os = "dragonfly",
+    target_os = "freebsd", target_os = "ios", target_os = "macos",
+    target_os = "netbsd", target_os = "openbsd"))]
+fn precise_timeouts() -> bool {
+    true
+}
+
+#[cfg(not(any(target_os = "bitrig", target_os = "dragonfly",
+    target_os = "freebsd", target_os = "ios", target_os = "macos",
+    target_os = "netbsd", target_os = "openbsd",
+    all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64",
+        target_arch = "arm", target_arch = "aarch64", target_arch = "mips",
+        target_arch = "mips64", target_arch = "powerpc", target_arch = "powerpc64",
+        target_arch = "s390x", target_arch = "riscv64")))))]
+fn precise_timeouts() -> bool {
+    false
+}
+
+// kqueue takes a `timespec`, so the timeout is not rounded to milliseconds
+#[cfg(any(target_os = "bitrig", target_os = "dragonfly",
+    target_os = "freebsd", target_os = "ios", target_os = "macos",
+    target_os = "netbsd", target_os = "openbsd"))]
+#[test]
+pub fn test_poll_timeout_precise_kqueue() {
+    let mut poll = Poll::new().unwrap();
+    let mut min_us = u64::max_value();
+
+    // Keep the fastest run, so that scheduling delays don't fail the test
+    for _ in 0..10 {
+        let start = precise_time_ns();
+        assert_eq!(0, poll.poll(Some(Duration::new(0, 500_000))).unwrap());
+        let elapsed_us = (precise_time_ns() - start) / 1_000;
+
+        assert!(elapsed_us >= 500, "elapsed={}us", elapsed_us);
+
+        if elapsed_us < min_us {
+            min_us = elapsed_us;
+        }
+    }
+
+    assert!(min_us < 1_000, "timeout was rounded up; elapsed={}us", min_us);
+}
+
+#[test]
+pub fn test_poll_try_clone() {
+    use std::thread;
+
+    let mut poll = Poll::new().unwrap();
+    let mut clone = poll.try_clone().unwrap();
+
+    let sock = udp_socket();
+    poll.register(&sock, Token(0), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    // Poll the clone from another thread
+    let handle = thread::spawn(move || {
+        assert_eq!(1, clone.poll(Some(Duration::from_millis(1_000))).unwrap());
+        assert_eq!(Token(0), clone.events().get(0).unwrap().token());
+    });
+
+    handle.join().unwrap();
+}
+
+#[test]
+pub fn test_sort_by_token() {
+    let mut poll = Poll::new().unwrap();
+
+    let socks: Vec<UdpSocket> = (0..3).map(|_| udp_socket()).collect();
+
+    // Register in reverse token order
+    for (i, sock) in socks.iter().enumerate() {
+        poll.register(sock, Token(10 - i), EventSet::writable(), PollOpt::edge()).unwrap();
+    }
+
+    assert_eq!(3, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+
+    poll.sort_by_token();
+
+    let tokens: Vec<Token> = poll.events().map(|e| e.token()).collect();
+    assert_eq!(vec![Token(8), Token(9), Token(10)], tokens);
+}
+
+#[test]
+pub fn test_poll_fairness() {
+    use std::collections::HashSet;
+
+    let mut poll = Poll::new().unwrap();
+    poll.set_fairness(true);
+
+    let socks: Vec<UdpSocket> = (0..3).map(|_| udp_socket()).collect();
+
+    // Level triggered, so every socket is reported on each poll
+    for (i, sock) in socks.iter().enumerate() {
+        poll.register(sock, Token(i), EventSet::writable(), PollOpt::level()).unwrap();
+    }
+
+    let mut last = HashSet::new();
+
+    for _ in 0..6 {
+        assert_eq!(3, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+        last.insert(poll.events().last().unwrap().token());
+    }
+
+    assert_eq!(3, last.len());
+}
+
+// Windows may return deferred events in excess of the budget
+#[cfg(unix)]
+#[test]
+pub fn test_poll_budget() {
+    let mut poll = Poll::new().unwrap();
+
+    let socks: Vec<UdpSocket> = (0..100).map(|_| udp_socket()).collect();
+
+    // Level triggered, so the sockets left out of a batch stay ready
+    for (i, sock) in socks.iter().enumerate() {
+        poll.register(sock, Token(i), EventSet::writable(), PollOpt::level()).unwrap();
+    }
+
+    let n = poll.poll_budget(Some(Duration::from_millis(1_000)), 10).unwrap();
+    assert!(n > 0 && n <= 10, "returned {} events", n);
+    assert_eq!(n, poll.events().count());
+    assert!(poll.had_more());
+
+    // Going back to `poll` uses the full capacity again
+    assert_eq!(100, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+    assert!(!poll.had_more());
+
+    let n = poll.poll_budget(Some(Duration::from_millis(1_000)), 10).unwrap();
+    assert!(n > 0 && n <= 10, "returned {} events", n);
+}
+
+#[cfg(unix)]
+#[test]
+pub fn test_register_clone_under_second_token() {
+    let mut poll = Poll::new().unwrap();
+
+    let tx = udp_socket();
+    let rx = udp_socket();
+    let dup = rx.try_clone().unwrap();
+
+    poll.register(&rx, Token(0), EventSet::readable(), PollOpt::edge()).unwrap();
+    poll.register(&dup, Token(1), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+    assert_eq!(Token(1), poll.events().get(0).unwrap().token());
+
+    // Removing the write token leaves the read token registered
+    poll.deregister(&dup).unwrap();
+
+    tx.send_to(b"hello", &rx.local_addr().unwrap()).unwrap();
+
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+
+    let event = poll.events().get(0).unwrap();
+    assert_eq!(Token(0), event.token());
+    assert!(event.kind().is_readable());
+}
+
+#[test]
+pub fn test_poll_huge_timeout() {
+    use std::u64;
+
+    let mut poll = Poll::new().unwrap();
+
+    let sock = udp_socket();
+    poll.register(&sock, Token(0), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    // The timeout is clamped rather than overflowing, and the ready socket
+    // is returned right away
+    assert_eq!(1, poll.poll(Some(Duration::from_secs(u64::MAX / 2))).unwrap());
+    assert_eq!(Token(0), poll.events().get(0).unwrap().token());
+}
+
+#[test]
+pub fn test_poll_iter() {
+    let mut poll = Poll::new().unwrap();
+
+    let tx = udp_socket();
+    let rx = udp_socket();
+
+    poll.register(&rx, Token(3), EventSet::readable(), PollOpt::edge()).unwrap();
+
+    tx.send_to(b"hello", &rx.local_addr().unwrap()).unwrap();
+
+    let events: Vec<Event> = poll.poll_iter(Some(Duration::from_millis(1_000))).unwrap().collect();
+
+    assert_eq!(1, events.len());
+    assert_eq!(Token(3), events[0].token());
+    assert!(events[0].is_readable());
+}
+
+#[test]
+pub fn test_poll_had_more() {
+    use std::collections::HashSet;
+
+    let mut poll = Poll::with_capacity(1).unwrap();
+
+    let socks: Vec<UdpSocket> = (0..3).map(|_| udp_socket()).collect();
+
+    for (i, sock) in socks.iter().enumerate() {
+        poll.register(sock, Token(i), EventSet::writable(), PollOpt::edge()).unwrap();
+    }
+
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+    assert!(poll.had_more());
+
+    let mut tokens = HashSet::new();
+    tokens.insert(poll.events().get(0).unwrap().token());
+
+    // Drain the rest without blocking
+    while poll.had_more() {
+        for event in poll.poll_iter(Some(Duration::from_millis(0))).unwrap() {
+            tokens.insert(event.token());
+        }
+    }
+
+    assert_eq!(3, tokens.len());
+    assert!(!poll.had_more());
+}
+
+#[cfg(unix)]
+#[test]
+pub fn test_deregister_after_close() {
+    use mio::unix::{pipe, EventedFd};
+    use std::os::unix::io::AsRawFd;
+
+    let mut poll = Poll::new().unwrap();
+
+    let (_rd, wr) = pipe().unwrap();
+    let fd = wr.as_raw_fd();
+
+    poll.register(&EventedFd(&fd), Token(0), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    // Close the fd before deregistering it
+    drop(wr);
+    poll.deregister(&EventedFd(&fd)).unwrap();
+
+    assert_eq!(0, poll.poll_now().unwrap());
+
+    // The token can be reused right away
+    let sock = udp_socket();
+    poll.register(&sock, Token(0), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+    assert_eq!(Token(0), poll.events().get(0).unwrap().token());
+}
+
+#[test]
+pub fn test_poll_coalesces_per_token() {
+    let mut poll = Poll::new().unwrap();
+
+    let tx = udp_socket();
+    let rx = udp_socket();
+
+    poll.register(&rx, Token(0), EventSet::readable() | EventSet::writable(), PollOpt::level()).unwrap();
+
+    tx.send_to(b"hello", &rx.local_addr().unwrap()).unwrap();
+
+    loop {
+        let n = poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+
+        // Readable and writable are never split across events
+        assert_eq!(1, n);
+
+        let event = poll.events().get(0).unwrap();
+        assert_eq!(Token(0), event.token());
+        assert!(event.is_writable());
+
+        if event.is_readable() {
+            break;
+        }
+    }
+}
+
+#[test]
+pub fn test_reregister_batch() {
+    let mut poll = Poll::new().unwrap();
+
+    let socks: Vec<UdpSocket> = (0..10).map(|_| udp_socket()).collect();
+
+    for (i, sock) in socks.iter().enumerate() {
+        poll.register(sock, Token(i), EventSet::readable(), PollOpt::edge()).unwrap();
+    }
+
+    // Nothing has been received
+    assert_eq!(0, poll.poll_now().unwrap());
+
+    let changes: Vec<(&Evented, Token, EventSet, PollOpt)> = socks.iter().enumerate().map(|(i, sock)| {
+        (sock as &Evented, Token(i), EventSet::writable(), PollOpt::edge())
+    }).collect();
+
+    poll.reregister_batch(&changes).unwrap();
+
+    let mut tokens = vec![];
+
+    while tokens.len() < socks.len() {
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+
+        for event in poll.events() {
+            assert!(event.is_writable());
+            tokens.push(event.token());
+        }
+    }
+
+    tokens.sort();
+    assert_eq!((0..10).map(Token).collect::<Vec<Token>>(), tokens);
+}
+
+#[test]
+pub fn test_poll_with() {
+    let mut poll = Poll::new().unwrap();
+
+    let a = udp_socket();
+    let b = udp_socket();
+
+    poll.register(&a, Token(0), EventSet::writable(), PollOpt::edge()).unwrap();
+    poll.register(&b, Token(1), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    let mut tokens = vec![];
+
+    while tokens.len() < 2 {
+        let n = poll.poll_with(Some(Duration::from_millis(1_000)), |event| {
+            assert!(event.is_writable());
+            tokens.push(event.token());
+        }).unwrap();
+
+        assert!(n <= 2);
+    }
+
+    tokens.sort();
+    assert_eq!(vec![Token(0), Token(1)], tokens);
+}
+
+#[cfg(unix)]
+#[test]
+pub fn test_register_owned() {
+    let mut poll = Poll::new().unwrap();
+
+    let sock = udp_socket();
+
+    {
+        // Register a clone, so that closing it doesn't by itself remove the
+        // socket from the selector
+        let registered = poll.register_owned(sock.try_clone().unwrap(), Token(0), EventSet::writable(), PollOpt::level()).unwrap();
+        assert_eq!(sock.local_addr().unwrap(), registered.get_ref().local_addr().unwrap());
+
+        assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+        assert_eq!(Token(0), poll.events().get(0).unwrap().token());
+    }
+
+    // Dropping the handle deregistered it
+    assert_eq!(0, poll.poll(Some(Duration::from_millis(100))).unwrap());
+
+    let registered = poll.register_owned(sock, Token(1), EventSet::writable(), PollOpt::level()).unwrap();
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+
+    let sock = registered.into_inner().unwrap();
+    assert_eq!(0, poll.poll(Some(Duration::from_millis(100))).unwrap());
+
+    poll.register(&sock, Token(2), EventSet::writable(), PollOpt::level()).unwrap();
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+}
+
+#[test]
+pub fn test_poll_tick() {
+    let mut poll = Poll::new().unwrap();
+    poll.set_tick(Token(7), Duration::from_millis(20));
+
+    let start = precise_time_ns();
+    let mut ticks = 0;
+
+    while precise_time_ns() - start < 100_000_000 {
+        // Nothing is registered, ticks wake up the poll on their own
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+
+        for event in poll.events() {
+            assert_eq!(Token(7), event.token());
+            assert!(event.is_readable());
+            ticks += 1;
+        }
+    }
+
+    assert!(ticks >= 3 && ticks <= 6, "ticks={}", ticks);
+
+    poll.clear_tick();
+    assert_eq!(0, poll.poll(Some(Duration::from_millis(50))).unwrap());
+}
+
+#[test]
+pub fn test_poll_label() {
+    use std::sync::Arc;
+
+    let mut poll = Poll::new().unwrap();
+
+    let sock = udp_socket();
+    poll.register(&sock, Token(0), EventSet::writable(), PollOpt::edge()).unwrap();
+    poll.set_label(Token(0), Arc::new("udp sender"));
+
+    assert!(poll.label(Token(1)).is_none());
+
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+
+    let token = poll.events().get(0).unwrap().token();
+    let label = poll.label(token).unwrap();
+    assert_eq!(Some(&"udp sender"), label.downcast_ref::<&'static str>());
+
+    assert!(poll.remove_label(token).is_some());
+    assert!(poll.label(token).is_none());
+}
+
+#[test]
+pub fn test_poll_one() {
+    let mut poll = Poll::new().unwrap();
+
+    let a = udp_socket();
+    let b = udp_socket();
+
+    poll.register(&a, Token(0), EventSet::writable(), PollOpt::edge()).unwrap();
+    poll.register(&b, Token(1), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    // Both sockets are ready, but only one event is returned at a time
+    let first = poll.poll_one(Some(Duration::from_millis(1_000))).unwrap().unwrap();
+    let second = poll.poll_one(Some(Duration::from_millis(1_000))).unwrap().unwrap();
+
+    assert!(first.is_writable());
+    assert!(second.is_writable());
+
+    let mut tokens = vec![first.token(), second.token()];
+    tokens.sort();
+    assert_eq!(vec![Token(0), Token(1)], tokens);
+
+    assert!(poll.poll_one(Some(Duration::from_millis(0))).unwrap().is_none());
+}
+
+#[cfg(unix)]
+#[test]
+pub fn test_poll_auto_deregister() {
+    let mut poll = Poll::new().unwrap();
+
+    let sock = udp_socket();
+    poll.register(&sock, Token(0), EventSet::writable(), PollOpt::level() | PollOpt::auto_deregister()).unwrap();
+
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+    assert_eq!(Token(0), poll.events().get(0).unwrap().token());
+
+    // The socket is still writable, but no longer registered
+    assert_eq!(0, poll.poll(Some(Duration::from_millis(100))).unwrap());
+
+    // So it can be registered again
+    poll.register(&sock, Token(1), EventSet::writable(), PollOpt::level()).unwrap();
+
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+    assert_eq!(Token(1), poll.events().get(0).unwrap().token());
+}
+
+// Registering twice fails on epoll only, kqueue updates the registration
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_poll_auto_deregister_register_twice() {
+    let mut poll = Poll::new().unwrap();
+
+    let sock = udp_socket();
+    poll.register(&sock, Token(0), EventSet::writable(), PollOpt::level() | PollOpt::auto_deregister()).unwrap();
+
+    // The failed registration leaves the first one, and its tracking, alone
+    assert!(poll.register(&sock, Token(1), EventSet::writable(), PollOpt::level()).is_err());
+
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+    assert_eq!(Token(0), poll.events().get(0).unwrap().token());
+
+    assert_eq!(0, poll.poll(Some(Duration::from_millis(100))).unwrap());
+    poll.register(&sock, Token(1), EventSet::writable(), PollOpt::level()).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+pub fn test_poll_auto_deregister_from_clone() {
+    let mut poll = Poll::new().unwrap();
+    let mut clone = poll.try_clone().unwrap();
+
+    let sock = udp_socket();
+    poll.register(&sock, Token(0), EventSet::writable(), PollOpt::edge() | PollOpt::auto_deregister()).unwrap();
+
+    // The event is received by the clone, which deregisters the socket for
+    // both instances
+    assert_eq!(1, clone.poll(Some(Duration::from_millis(1_000))).unwrap());
+    assert_eq!(0, poll.poll(Some(Duration::from_millis(100))).unwrap());
+
+    poll.register(&sock, Token(1), EventSet::writable(), PollOpt::edge()).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_poll_auto_deregister_exclusive() {
+    let mut poll = Poll::new().unwrap();
+
+    let sock = udp_socket();
+    poll.register(&sock, Token(0), EventSet::writable(),
+                  PollOpt::level() | PollOpt::exclusive() | PollOpt::auto_deregister()).unwrap();
+
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+    assert_eq!(0, poll.poll(Some(Duration::from_millis(100))).unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+pub fn test_poll_pipe() {
+    use mio::unix::pipe;
+    use std::io::{ErrorKind, Read, Write};
+
+    let mut poll = Poll::new().unwrap();
+    let (mut rd, mut wr) = pipe().unwrap();
+
+    poll.register(&rd, Token(0), EventSet::readable(), PollOpt::edge()).unwrap();
+
+    let mut buf = [0; 16];
+    assert_eq!(ErrorKind::WouldBlock, rd.read(&mut buf).unwrap_err().kind());
+    assert_eq!(0, poll.poll_now().unwrap());
+
+    wr.write_all(b"ping").unwrap();
+
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+
+    let event = poll.events().get(0).unwrap();
+    assert_eq!(Token(0), event.token());
+    assert!(event.is_readable());
+
+    assert_eq!(4, rd.read(&mut buf).unwrap());
+    assert_eq!(b"ping", &buf[..4]);
+}
+
+#[cfg(feature = "timestamp")]
+#[test]
+pub fn test_event_timestamp() {
+    let mut poll = Poll::new().unwrap();
+
+    let sock = udp_socket();
+    poll.register(&sock, Token(0), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    let before = precise_time_ns();
+    assert_eq!(1, poll.poll(Some(Duration::from_millis(1_000))).unwrap());
+
+    let event = poll.events().get(0).unwrap();
+    let now = precise_time_ns();
+
+    assert!(before <= event.timestamp());
+    assert!(event.timestamp() <= now);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_poll_cpu_affinity() {
+    use std::io::ErrorKind;
+    use std::thread;
+
+    // Pin a scratch thread so the test runner's threads are unaffected
+    thread::spawn(|| {
+        let poll = Poll::new().unwrap();
+
+        // CPUs outside of the allowed cpuset, as is common in containers, are
+        // rejected with EINVAL, so pin to the first allowed one
+        let mut pinned = false;
+
+        for cpu in 0..1024 {
+            match poll.set_cpu_affinity(cpu) {
+                Ok(()) => {
+                    pinned = true;
+                    break;
+                }
+                Err(e) => assert_eq!(ErrorKind::InvalidInput, e.kind()),
+            }
+        }
+
+        assert!(pinned, "no CPU could be pinned");
+        assert!(poll.set_cpu_affinity(1 << 20).is_err());
+    }).join().unwrap();
+}
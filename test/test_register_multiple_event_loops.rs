@@ -69,3 +69,38 @@ fn test_udp_register_multiple_event_loops() {
     assert!(res.is_err());
     assert_eq!(res.unwrap_err().kind(), ErrorKind::Other);
 }
+
+#[cfg(unix)]
+#[test]
+fn test_pipe_register_multiple_event_loops() {
+    use mio::unix::pipe;
+
+    let (rd, _wr) = pipe().unwrap();
+
+    let mut event_loop_1 = EventLoop::<MyHandler>::new().unwrap();
+    event_loop_1.register(&rd, Token(0), EventSet::readable(), PollOpt::edge()).unwrap();
+
+    let mut event_loop_2 = EventLoop::<MyHandler>::new().unwrap();
+
+    // Try registering the same pipe with the initial one
+    let res = event_loop_2.register(&rd, Token(0), EventSet::readable(), PollOpt::edge());
+    assert!(res.is_err());
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::Other);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unix_io_is_sync() {
+    use mio::Io;
+    use mio::unix::{PipeReader, PipeWriter, UnixListener, UnixSocket, UnixStream};
+
+    fn assert_sync<T: Sync>() {}
+
+    // Tracking the selector must not make the handles `!Sync`
+    assert_sync::<Io>();
+    assert_sync::<PipeReader>();
+    assert_sync::<PipeWriter>();
+    assert_sync::<UnixSocket>();
+    assert_sync::<UnixStream>();
+    assert_sync::<UnixListener>();
+}
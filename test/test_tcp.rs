@@ -249,6 +249,537 @@ fn listen_then_close() {
     e.run_once(&mut h, Some(Duration::from_millis(100))).unwrap();
 }
 
+#[test]
+fn listener_ttl() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+    l.set_ttl(100).unwrap();
+    assert_eq!(100, l.ttl().unwrap());
+}
+
+#[test]
+fn listener_dual_stack() {
+    use net2::TcpBuilder;
+    use mio::{Poll, Ipv4Addr};
+
+    // `IPV6_V6ONLY` has to be configured before the socket is bound
+    let builder = TcpBuilder::new_v6().unwrap();
+    builder.only_v6(false).unwrap();
+    builder.bind("[::]:0").unwrap();
+
+    let l = builder.listen(1024).unwrap();
+    let addr = l.local_addr().unwrap();
+    let l = TcpListener::from_listener(l, &addr).unwrap();
+
+    assert!(!l.only_v6().unwrap());
+
+    let mut poll = Poll::new().unwrap();
+    poll.register(&l, Token(0), EventSet::readable(), PollOpt::edge()).unwrap();
+
+    // Connect over IPv4, the connection arrives as a v4-mapped address
+    let v4 = format!("127.0.0.1:{}", addr.port()).parse().unwrap();
+    let _s = TcpStream::connect(&v4).unwrap();
+
+    let peer;
+
+    loop {
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+
+        if let Some((_, a)) = l.accept().unwrap() {
+            peer = a;
+            break;
+        }
+    }
+
+    match peer {
+        net::SocketAddr::V6(ref a) => {
+            assert_eq!(Some(Ipv4Addr::new(127, 0, 0, 1)), a.ip().to_ipv4());
+        }
+        net::SocketAddr::V4(..) => panic!("expected a v4-mapped address"),
+    }
+}
+
+#[test]
+fn connecting() {
+    use mio::Poll;
+    use mio::tcp::Connecting;
+
+    let l = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let t = thread::spawn(move || {
+        l.accept().unwrap();
+    });
+
+    let mut poll = Poll::new().unwrap();
+    let mut c = Connecting::connect(&addr).unwrap();
+
+    poll.register(&c, Token(1), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    let s;
+
+    loop {
+        if let Some(stream) = c.poll_connect().unwrap() {
+            s = stream;
+            break;
+        }
+
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+    }
+
+    assert_eq!(addr, s.peer_addr().unwrap());
+
+    // The stream has already been handed out
+    assert!(c.poll_connect().is_err());
+
+    t.join().unwrap();
+}
+
+#[test]
+fn connecting_refused() {
+    use mio::Poll;
+    use mio::tcp::Connecting;
+    use std::io::ErrorKind;
+
+    // Grab a free port, nothing listens on it once the listener is dropped
+    let addr = {
+        let l = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        l.local_addr().unwrap()
+    };
+
+    let mut poll = Poll::new().unwrap();
+    let mut c = Connecting::connect(&addr).unwrap();
+
+    poll.register(&c, Token(1), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    let err;
+
+    loop {
+        match c.poll_connect() {
+            Ok(Some(..)) => panic!("connected to a closed port"),
+            Ok(None) => {}
+            Err(e) => {
+                err = e;
+                break;
+            }
+        }
+
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+    }
+
+    assert_eq!(ErrorKind::ConnectionRefused, err.kind());
+
+    // The failure is reported again rather than waiting forever
+    for _ in 0..3 {
+        let again = c.poll_connect().unwrap_err();
+        assert_eq!(ErrorKind::ConnectionRefused, again.kind());
+    }
+}
+
+#[test]
+fn peek() {
+    use mio::Poll;
+    use std::io::ErrorKind;
+
+    let l = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let (tx, rx) = channel();
+    let t = thread::spawn(move || {
+        let mut s = l.accept().unwrap().0;
+        rx.recv().unwrap();
+        s.write_all(b"hello").unwrap();
+        rx.recv().unwrap();
+    });
+
+    let mut poll = Poll::new().unwrap();
+    let mut s = TcpStream::connect(&addr).unwrap();
+
+    poll.register(&s, Token(1), EventSet::readable(), PollOpt::level()).unwrap();
+
+    let mut buf = [0; 16];
+
+    // Nothing has been written yet
+    assert_eq!(ErrorKind::WouldBlock, s.peek(&mut buf).unwrap_err().kind());
+
+    tx.send(()).unwrap();
+
+    loop {
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+
+        match s.peek(&mut buf) {
+            Ok(n) if n == 5 => break,
+            Ok(_) => {}
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    assert_eq!(b"hello", &buf[..5]);
+
+    // Reading returns the same bytes
+    let mut read = [0; 16];
+    assert_eq!(Some(5), s.try_read(&mut read).unwrap());
+    assert_eq!(b"hello", &read[..5]);
+
+    tx.send(()).unwrap();
+    t.join().unwrap();
+}
+
+#[test]
+fn addrs_after_register() {
+    use mio::Poll;
+
+    let mut poll = Poll::new().unwrap();
+
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    poll.register(&l, Token(0), EventSet::readable(), PollOpt::edge()).unwrap();
+
+    let client = TcpStream::connect(&addr).unwrap();
+    poll.register(&client, Token(1), EventSet::writable(), PollOpt::edge()).unwrap();
+
+    let server;
+
+    loop {
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+
+        if let Some((s, _)) = l.accept().unwrap() {
+            server = s;
+            break;
+        }
+    }
+
+    poll.register(&server, Token(2), EventSet::readable(), PollOpt::edge()).unwrap();
+
+    assert_eq!(addr, l.local_addr().unwrap());
+    assert_eq!(addr, client.peer_addr().unwrap());
+    assert_eq!(addr, server.local_addr().unwrap());
+    assert_eq!(client.local_addr().unwrap(), server.peer_addr().unwrap());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn listener_exclusive_wakeup() {
+    use mio::Poll;
+
+    const POLLERS: usize = 4;
+
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    // Each acceptor gets its own handle to the listening socket
+    let handles: Vec<_> = (0..POLLERS).map(|i| {
+        let l = l.try_clone().unwrap();
+
+        thread::spawn(move || {
+            let mut poll = Poll::new().unwrap();
+            poll.register(&l, Token(i), EventSet::readable(),
+                          PollOpt::edge() | PollOpt::exclusive()).unwrap();
+            poll.poll(Some(Duration::from_millis(500))).unwrap()
+        })
+    }).collect();
+
+    // Give the threads time to block in `poll`
+    ::sleep_ms(100);
+    let _s = net::TcpStream::connect(&addr).unwrap();
+
+    let woken = handles.into_iter().fold(0, |n, h| n + h.join().unwrap());
+
+    // `EPOLLEXCLUSIVE` wakes "one or more" of the waiters, but not all of them
+    assert!(woken >= 1 && woken < POLLERS, "woken={}", woken);
+}
+
+#[test]
+fn write_all_read_exact() {
+    use mio::Poll;
+
+    const N: usize = 16 * 1024 * 1024;
+
+    let l = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = l.local_addr().unwrap();
+
+    // Echo everything back once it has all been received
+    let t = thread::spawn(move || {
+        let mut s = l.accept().unwrap().0;
+        let mut buf = vec![0; N];
+        s.read_exact(&mut buf).unwrap();
+        s.write_all(&buf).unwrap();
+    });
+
+    let mut poll = Poll::new().unwrap();
+    let mut s = TcpStream::connect(&addr).unwrap();
+
+    poll.register(&s, Token(1), EventSet::writable(), PollOpt::level()).unwrap();
+
+    let src: Vec<u8> = (0..N).map(|i| i as u8).collect();
+    let mut pos = 0;
+    let mut partial = false;
+
+    while !s.try_write_all(&src, &mut pos).unwrap() {
+        // The write can't go through in one call
+        partial = true;
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+    }
+
+    assert!(partial);
+    assert_eq!(N, pos);
+
+    poll.reregister(&s, Token(1), EventSet::readable(), PollOpt::level()).unwrap();
+
+    let mut dst = vec![0; N];
+    let mut pos = 0;
+
+    while !s.try_read_exact(&mut dst, &mut pos).unwrap() {
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+    }
+
+    assert_eq!(N, pos);
+    assert!(src == dst);
+
+    t.join().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn hup_with_readable_interest() {
+    use mio::Poll;
+
+    let l = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let t = thread::spawn(move || {
+        // Close the connection right away
+        drop(l.accept().unwrap());
+    });
+
+    let mut poll = Poll::new().unwrap();
+    let s = TcpStream::connect(&addr).unwrap();
+
+    // No explicit `hup` interest
+    poll.register(&s, Token(1), EventSet::readable(), PollOpt::level()).unwrap();
+
+    loop {
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+
+        if let Some(event) = poll.events().get(0) {
+            assert!(event.is_readable());
+            assert!(event.is_hup(), "events={:?}", event.kind());
+            break;
+        }
+    }
+
+    t.join().unwrap();
+    drop(s);
+}
+
+#[test]
+fn nodelay() {
+    use mio::Poll;
+
+    let l = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let t = thread::spawn(move || {
+        l.accept().unwrap();
+    });
+
+    let s = TcpStream::connect(&addr).unwrap();
+
+    // The option can be set while the connect is still in progress
+    s.set_nodelay(true).unwrap();
+    assert!(s.nodelay().unwrap());
+
+    let mut poll = Poll::new().unwrap();
+    poll.register(&s, Token(1), EventSet::writable(), PollOpt::edge()).unwrap();
+    poll.poll(None).unwrap();
+
+    assert!(s.nodelay().unwrap());
+    s.set_nodelay(false).unwrap();
+    assert!(!s.nodelay().unwrap());
+
+    t.join().unwrap();
+}
+
+#[test]
+fn poll_write_ready() {
+    use mio::Poll;
+
+    let l = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let (tx, rx) = channel();
+    let t = thread::spawn(move || {
+        // Accept but never read, so that the send buffer fills up
+        let _s = l.accept().unwrap().0;
+        rx.recv().unwrap();
+    });
+
+    let mut poll = Poll::new().unwrap();
+    let mut s = TcpStream::connect(&addr).unwrap();
+
+    poll.register(&s, Token(1), EventSet::writable(), PollOpt::level()).unwrap();
+
+    while !s.poll_write_ready().unwrap() {
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+    }
+
+    let buf = [0; 1024];
+
+    while let Some(_) = s.try_write(&buf).unwrap() {
+    }
+
+    assert!(!s.poll_write_ready().unwrap());
+
+    tx.send(()).unwrap();
+    t.join().unwrap();
+}
+
+#[test]
+fn accept_all() {
+    use mio::Poll;
+
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let mut poll = Poll::new().unwrap();
+    poll.register(&l, Token(0), EventSet::readable(), PollOpt::edge()).unwrap();
+
+    let clients: Vec<net::TcpStream> = (0..5).map(|_| {
+        net::TcpStream::connect(&addr).unwrap()
+    }).collect();
+
+    let mut conns = vec![];
+
+    while conns.len() < clients.len() {
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+        l.accept_all(&mut conns).unwrap();
+    }
+
+    assert_eq!(5, conns.len());
+
+    // Nothing is left pending
+    assert_eq!(0, l.accept_all(&mut conns).unwrap());
+}
+
+#[test]
+fn fd_exhaustion() {
+    use mio::tcp::is_fd_exhaustion;
+    use std::io;
+
+    for &code in fd_exhaustion_codes() {
+        assert!(is_fd_exhaustion(&io::Error::from_raw_os_error(code)));
+    }
+
+    let would_block = io::Error::new(io::ErrorKind::WouldBlock, "would block");
+    assert!(!is_fd_exhaustion(&would_block));
+}
+
+#[cfg(unix)]
+fn fd_exhaustion_codes() -> &'static [i32] {
+    use libc::{EMFILE, ENFILE};
+
+    static CODES: [i32; 2] = [EMFILE, ENFILE];
+    &CODES
+}
+
+// WSAEMFILE
+#[cfg(windows)]
+fn fd_exhaustion_codes() -> &'static [i32] {
+    &[10024]
+}
+
+// Set in the child process spawned by `fd_exhaustion_accept`
+#[cfg(unix)]
+const FD_EXHAUSTION_CHILD: &'static str = "MIO_TEST_FD_EXHAUSTION_CHILD";
+
+// Lowering RLIMIT_NOFILE would starve the other tests of descriptors, so the
+// test re-runs itself in a child process, which does the actual work
+#[test]
+#[cfg(unix)]
+fn fd_exhaustion_accept() {
+    use std::env;
+    use std::process::Command;
+
+    if env::var_os(FD_EXHAUSTION_CHILD).is_some() {
+        return fd_exhaustion_accept_child();
+    }
+
+    let out = Command::new(env::current_exe().unwrap())
+        .arg("fd_exhaustion_accept")
+        .env(FD_EXHAUSTION_CHILD, "1")
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "child failed: {}", stdout);
+    // Guards against the filter not matching the test at all
+    assert!(stdout.contains("fd_exhaustion_accept ... ok"), "child did not run: {}", stdout);
+}
+
+#[cfg(unix)]
+fn fd_exhaustion_accept_child() {
+    use libc;
+    use mio::tcp::is_fd_exhaustion;
+    use std::os::unix::io::AsRawFd;
+
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    // The connection is queued on the listener before the fd table fills up
+    let _client = net::TcpStream::connect(&addr).unwrap();
+
+    unsafe {
+        let mut lim: libc::rlimit = ::std::mem::zeroed();
+        assert_eq!(0, libc::getrlimit(libc::RLIMIT_NOFILE, &mut lim));
+        lim.rlim_cur = ::std::cmp::min(lim.rlim_cur, 64);
+        assert_eq!(0, libc::setrlimit(libc::RLIMIT_NOFILE, &lim));
+
+        while libc::dup(l.as_raw_fd()) >= 0 {}
+    }
+
+    let mut conns = vec![];
+    let err = l.accept_all(&mut conns).unwrap_err();
+
+    assert!(is_fd_exhaustion(&err), "unexpected error: {:?}", err);
+    assert!(conns.is_empty());
+}
+
+#[test]
+fn accept_nodelay() {
+    use mio::Poll;
+    use std::io::ErrorKind;
+
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let mut poll = Poll::new().unwrap();
+    poll.register(&l, Token(0), EventSet::readable(), PollOpt::level()).unwrap();
+
+    for &nodelay in &[true, false] {
+        l.set_accept_nodelay(nodelay);
+
+        let _c = net::TcpStream::connect(&addr).unwrap();
+        let mut s;
+
+        loop {
+            poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+
+            if let Some((conn, _)) = l.accept().unwrap() {
+                s = conn;
+                break;
+            }
+        }
+
+        assert_eq!(nodelay, s.nodelay().unwrap());
+
+        // Nothing was written, so a nonblocking read fails right away
+        let mut buf = [0; 16];
+        assert_eq!(ErrorKind::WouldBlock, s.read(&mut buf).unwrap_err().kind());
+    }
+}
+
 fn assert_send<T: Send>() {
 }
 
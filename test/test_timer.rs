@@ -152,3 +152,50 @@ pub fn test_timer() {
 
     assert!(handler.state == AfterHup, "actual={:?}", handler.state);
 }
+
+#[test]
+pub fn test_interval() {
+    #[derive(Clone)]
+    enum Tick {
+        Interval,
+        Stop,
+    }
+
+    struct IntervalHandler {
+        fired: usize,
+    }
+
+    impl Handler for IntervalHandler {
+        type Timeout = Tick;
+        type Message = ();
+
+        fn timeout(&mut self, event_loop: &mut EventLoop<IntervalHandler>, tick: Tick) {
+            match tick {
+                Tick::Interval => self.fired += 1,
+                Tick::Stop => event_loop.shutdown(),
+            }
+        }
+    }
+
+    let mut builder = EventLoopBuilder::new();
+    builder.timer_tick(Duration::from_millis(1));
+
+    let mut event_loop = builder.build().unwrap();
+
+    let interval = event_loop.interval(Tick::Interval, Duration::from_millis(10)).unwrap();
+    event_loop.timeout(Tick::Stop, Duration::from_millis(35)).unwrap();
+
+    let mut handler = IntervalHandler { fired: 0 };
+    event_loop.run(&mut handler).unwrap();
+
+    assert!(handler.fired >= 3, "fired={}", handler.fired);
+
+    // Clearing the interval stops the recurrence
+    assert!(event_loop.clear_timeout(&interval));
+
+    let fired = handler.fired;
+    event_loop.timeout(Tick::Stop, Duration::from_millis(30)).unwrap();
+    event_loop.run(&mut handler).unwrap();
+
+    assert_eq!(fired, handler.fired);
+}
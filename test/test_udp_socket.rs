@@ -92,3 +92,31 @@ pub fn test_udp_socket() {
     info!("Starting event loop to test with...");
     event_loop.run(&mut UdpHandler::new(tx, rx, "hello world")).unwrap();
 }
+
+#[test]
+pub fn test_udp_recv_from_vec() {
+    use std::time::Duration;
+
+    let mut poll = Poll::new().unwrap();
+
+    let tx = UdpSocket::bound(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let rx = UdpSocket::bound(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+    poll.register(&rx, LISTENER, EventSet::readable(), PollOpt::edge()).unwrap();
+
+    assert!(rx.recv_from_vec(1024).unwrap().is_none());
+
+    let msg = [7; 300];
+    tx.send_to(&msg, &rx.local_addr().unwrap()).unwrap();
+
+    loop {
+        poll.poll(Some(Duration::from_millis(1_000))).unwrap();
+
+        if let Some((data, addr)) = rx.recv_from_vec(1024).unwrap() {
+            assert_eq!(300, data.len());
+            assert!(&data[..] == &msg[..]);
+            assert_eq!(tx.local_addr().unwrap(), addr);
+            break;
+        }
+    }
+}